@@ -1,19 +1,36 @@
+pub mod alerts;
+mod history;
+pub mod hooks;
 pub mod list_pointer;
-mod logging;
+pub mod logging;
+pub mod pause;
+pub mod status;
+pub mod suggest;
+pub mod summary;
+pub mod text_field;
 
 use crate::cli::Cli;
 use crate::config::{self, Config};
 use crate::routine::{
-    self,
-    task::{self, CompletionStatus, Task},
+    self, playlist, repeat,
+    task::{self, CompletionStatus, NewTask, Position, Task},
     Routine,
 };
+use alerts::{Alert, AlertTracker};
+use history::{Command, History};
+use hooks::{HookDetails, HookEvent};
 use list_pointer::ListPointer;
 use logging::{LogElement, RoutineLogger};
+use pause::Pauses;
+use status::Status;
+use suggest::{Suggestion, TaskHistory};
+use summary::Summary;
+use text_field::TextField;
 
-use chrono::{DateTime, Days, Local, MappedLocalTime};
+use chrono::{DateTime, Days, Local, NaiveTime};
+use color_eyre::eyre::{eyre, OptionExt, Result, WrapErr};
+use std::path::PathBuf;
 use std::time::{Duration, Instant};
-use tui_textarea::TextArea;
 
 /// Application.
 pub struct App {
@@ -21,7 +38,13 @@ pub struct App {
     /// should the application exit?
     pub should_quit: bool,
     pub debug: bool,
+    /// Whether to show only the current task, in big digits.
+    pub focus: bool,
     pub help_menu: bool,
+    /// how many lines the help menu is scrolled down
+    pub help_scroll: u16,
+    /// how many lines of the help menu fit on screen, as of the last draw
+    pub help_height: u16,
     /// counter
     pub counter: i64,
     /// task display widget
@@ -31,66 +54,108 @@ pub struct App {
     /// routine timer
     pub last_tick: Instant,
     logger: RoutineLogger,
+    history: History,
+    pub pauses: Pauses,
+    alerts: AlertTracker,
+    /// Index and name of the task the `task_start` hook last ran for.
+    started: Option<(usize, String)>,
+    /// Set once every task is done or skipped.
+    pub summary: Option<Summary>,
+    /// Where each routine in the session was loaded from, if they were loaded from files.
+    pub routine_paths: Vec<PathBuf>,
     pub start_time: DateTime<Local>,
     pub menu_focus: Mode,
-    pub text_input: TextArea<'static>,
+    pub text_input: TextField,
+    /// Which of the suggestions for the task being typed is selected.
+    pub suggestion: usize,
+    task_history: TaskHistory,
+    /// Time spent on tasks since the last break, for putting in the next one.
+    since_break: Duration,
 }
 
+/// What breaks put in by [`App::track_breaks`] are called.
+const BREAK_NAME: &str = "Break";
+
 impl App {
     /// Constructs a new instance of [`App`].
-    pub fn new(cli: Cli) -> App {
-        let routine_name = cli
-            .routine_path
-            .expect("Routine launcher not yet implemented. Please specify a routine path.");
-        let tasks =
-            Routine::with_tasks(routine::parse::read_csv().expect("Failed to load routine file"));
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the routine can't be loaded, the session log can't be created,
+    /// or the deadline doesn't exist on the local clock.
+    pub fn new(cli: Cli) -> Result<App> {
+        let paths = playlist::expand(&cli.routine_paths)?;
+        if paths.is_empty() {
+            return Err(eyre!(
+                "Routine launcher not yet implemented. Please specify a routine path."
+            ));
+        }
+        let mut config = config::load();
+        let mut loaded = vec![];
+        for (source, path) in paths.iter().enumerate() {
+            let tasks = routine::parse::read_csv(path, config.default_duration_unit)
+                .wrap_err_with(|| format!("Can't load {}", path.display()))?;
+            loaded.extend(tasks.into_iter().map(|task| Task { source, ..task }));
+        }
+        let tasks = Routine::with_tasks(loaded);
+        let names = playlist::routine_names(&paths);
+        // a chained session is logged under the names of all its routines, which can't have
+        // directories in them in a file name
+        let session_name = match paths.as_slice() {
+            [path] => path.to_string_lossy().into_owned(),
+            _ => names.join("+").replace(['/', '\\'], "_"),
+        };
+        let mut logger = RoutineLogger::new(&tasks, &Local::now(), &session_name)?;
+        logger.set_sources(names);
+        if let Some(budget) = cli.pause_budget {
+            // for this run, in place of every budget in the config file
+            config.pause_budget = Some(budget);
+            config.pause_budgets.clear();
+        }
+        let task_history = logger.task_history();
+        let mut app = Self::with_routine(config, tasks, logger);
+        app.routine_paths = paths;
+        app.task_history = task_history;
+
+        if let Some(deadline) = cli.deadline {
+            app.tasks
+                .set_deadline(next_deadline(Local::now(), deadline)?);
+        };
+        app.run_hook(HookEvent::RoutineStart, None, None);
+
+        Ok(app)
+    }
+
+    /// Constructs an [`App`] around an already loaded routine.
+    pub fn with_routine(config: Config, mut tasks: Routine, logger: RoutineLogger) -> App {
         let length = tasks.tasks.len();
-        let logger = RoutineLogger::new(&tasks, &Local::now(), &routine_name);
-        let mut app = Self {
-            config: config::load(),
-            text_input: TextArea::default(),
+        tasks.set_buffer(config.transition_buffer.0);
+        Self {
+            config,
+            text_input: TextField::default(),
+            suggestion: 0,
+            task_history: TaskHistory::default(),
             menu_focus: Mode::Navigation,
             start_time: Local::now(),
             should_quit: false,
             debug: false,
+            focus: false,
             help_menu: false,
+            help_scroll: 0,
+            help_height: 0,
             logger,
+            history: History::default(),
+            pauses: Pauses::default(),
+            alerts: AlertTracker::default(),
+            started: None,
+            summary: None,
+            routine_paths: vec![],
             counter: 0,
             tasks,
             task_widget_state: ListPointer::new(length),
             last_tick: Instant::now(),
-        };
-
-        let now = Local::now();
-        if let Some(deadline) = cli.deadline {
-            // TODO handle DST
-            let MappedLocalTime::Single(today_deadline) = now.with_time(deadline) else {
-                todo!("Handle DST");
-            };
-            let deadline = if today_deadline < now {
-                let Some(tomorrow) = now.checked_add_days(Days::new(1)) else {
-                    todo!("handle DST properly")
-                };
-                match tomorrow.with_time(deadline) {
-                    MappedLocalTime::Single(t) => t,
-                    _ => todo!(), // Risks crash around DST change
-                }
-            } else {
-                today_deadline
-            };
-            app.tasks.set_deadline(deadline);
-        };
-
-        /*
-        app.task_widget_state.select(
-            match app.tasks.len() {
-                0 => None,
-                _ => Some(0),
-            });
-        */
-        //app.task_widget_state.select(app.tasks.active).unwrap();
-
-        app
+            since_break: Duration::ZERO,
+        }
     }
 
     pub fn get_current_task_name(&self) -> Option<&str> {
@@ -98,6 +163,11 @@ impl App {
         self.tasks.get_nth(i).map(|t| t.name.as_str())
     }
 
+    pub fn get_current_task_remaining(&self) -> Option<Duration> {
+        let i = self.task_widget_state.selected();
+        self.tasks.get_nth(i).map(Task::remaining)
+    }
+
     pub fn get_total_remaining(&self) -> Duration {
         self.tasks.remaining()
     }
@@ -121,29 +191,258 @@ impl App {
         self.last_tick = this_tick;
 
         cli_log::debug!("Tick");
+        if matches!(self.menu_focus, Mode::Summary) {
+            // the routine is over, so there's nothing to spend time on
+            return;
+        }
+        if !self.tasks.is_complete() {
+            self.summary = None;
+        } else if self.summary.is_none() {
+            self.finish_routine();
+            return;
+        }
+        self.check_task_start();
         self.tasks.elapse(self.task_widget_state.selected(), delta);
         if let Some(t) = self.tasks.get_nth(self.task_widget_state.selected()) {
             self.logger.log(LogElement::elapsed(t, delta));
         }
+        self.track_breaks(delta);
+
+        let over_budget = self.pause_over_budget();
+        if self.pauses.should_warn(over_budget.is_some()) {
+            if let Some(pause) = self.pauses.current() {
+                let over = over_budget.filter(|d| !d.is_zero());
+                self.logger.log(LogElement::pause_over_budget(pause, over));
+            }
+        }
+
+        let selected = self.task_widget_state.selected();
+        let alerts = self.alerts.check(
+            self.config.alerts.deadline_risk,
+            selected.zip(self.tasks.get_nth(selected)),
+            self.tasks.compression(Local::now()),
+        );
+        for alert in alerts {
+            if matches!(alert, Alert::TimeUp(_)) {
+                self.run_hook(HookEvent::Overtime, selected, None);
+            }
+            if alert.enabled(&self.config.alerts) {
+                alerts::send(&self.config.alerts, &alert);
+            }
+        }
+
+        if let Some(dir) = self.status_dir() {
+            if let Err(e) = self.status().write(&dir) {
+                cli_log::warn!("Couldn't write the status to {}: {e}", dir.display());
+            }
+        }
+    }
+
+    /// Counts `delta` spent on the selected task toward the next break, and puts a break in
+    /// after the task once it's been long enough since the last one.
+    fn track_breaks(&mut self, delta: Duration) {
+        let Some(every) = self.config.breaks.every else {
+            return;
+        };
+        let selected = self.task_widget_state.selected();
+        let (Some(index), Some(task)) = (selected, self.tasks.get_nth(selected)) else {
+            return;
+        };
+        if task.is_break {
+            self.since_break = Duration::ZERO;
+            return;
+        }
+        self.since_break += delta;
+        if self.since_break < every.0 {
+            return;
+        }
+        let unfinished = |task: &Task| task.status == CompletionStatus::NotYet;
+        // one break at a time, and only with something to come back to after it
+        let waiting = self
+            .tasks
+            .tasks
+            .iter()
+            .any(|task| task.is_break && unfinished(task));
+        let more = self.tasks.tasks.iter().skip(index + 1).any(unfinished);
+        if waiting || !more {
+            return;
+        }
+        let mut rest = Task::new(BREAK_NAME, self.config.breaks.length.0.as_secs());
+        rest.is_break = true;
+        rest.rigid = self.config.breaks.rigid;
+        // not something to undo, so it isn't recorded, but what is has to make room for it
+        let rest = self.put_task(index + 1, rest);
+        self.logger.log(LogElement::break_put_in(&rest, index + 1));
+        self.history.make_room(index + 1);
+        self.since_break = Duration::ZERO;
+    }
+
+    /// Where to export the status to, if exporting it is turned on.
+    fn status_dir(&self) -> Option<PathBuf> {
+        self.config
+            .status
+            .enabled
+            .then(|| self.config.status.dir())
+            .flatten()
+    }
+
+    /// The state of the routine right now, for status bars.
+    pub fn status(&self) -> Status {
+        let task = self
+            .get_current_task_name()
+            .zip(self.get_current_task_remaining());
+        Status::new(
+            self.logger.routine_name(),
+            task,
+            self.get_projected_end_time(),
+            self.tasks.schedule_offset(Local::now()),
+            self.pauses.current().is_some(),
+            self.get_percentage_elapsed(),
+            self.config.clock_format.get_strftime(),
+        )
+    }
+
+    /// Runs the `task_start` hook if a task that isn't done yet has just been selected.
+    fn check_task_start(&mut self) {
+        let selected = self.task_widget_state.selected();
+        let current = selected
+            .zip(self.tasks.get_nth(selected))
+            .filter(|(_, task)| task.status == CompletionStatus::NotYet)
+            .map(|(index, task)| (index, task.name.clone()));
+        // while paused nothing is selected, and unpausing isn't starting the task again
+        if self.pauses.current().is_some() || current == self.started {
+            return;
+        }
+        if current.is_some() {
+            self.run_hook(HookEvent::TaskStart, selected, None);
+        }
+        self.started = current;
+    }
+
+    /// Runs the hook for `event`, if there is one, about the task at `index`.
+    fn run_hook(&self, event: HookEvent, index: Option<usize>, reason: Option<&str>) {
+        let details = HookDetails {
+            routine: self.logger.routine_name(),
+            task: self.tasks.get_nth(index),
+            remaining: self.get_total_remaining(),
+            projected_end: self.get_projected_end_time(),
+            reason,
+        };
+        hooks::run(&self.config.hooks, event, &details);
+    }
+
+    /// Shows and logs the summary of the routine.
+    fn finish_routine(&mut self) {
+        let now = Local::now();
+        let mut summary = Summary::new(
+            &self.tasks,
+            self.start_time,
+            now,
+            self.pauses.total(now),
+            self.get_percentage_elapsed(),
+            self.logger.past_totals(),
+        );
+        summary.past_per_routine = self.logger.past_routine_totals();
+        self.logger.log_summary(&summary);
+        self.summary = Some(summary);
+        self.menu_focus = Mode::Summary;
+        self.run_hook(HookEvent::RoutineEnd, None, None);
+    }
+
+    /// Leaves the summary to go back to the routine, for example to undo something.
+    pub fn back_to_routine(&mut self) {
+        self.menu_focus = Mode::Navigation;
+    }
+
+    /// Shows the summary again after going back to the routine, if every task is still done
+    /// or skipped.
+    pub fn reopen_summary(&mut self) {
+        if self.summary.is_some() {
+            self.menu_focus = Mode::Summary;
+        }
+    }
+
+    /// The names of the routines in the session, in order, told apart by their directories if
+    /// they have the same file name.
+    pub fn routine_names(&self) -> Vec<String> {
+        playlist::routine_names(&self.routine_paths)
+    }
+
+    /// Writes each routine, with any changes made during the session, back to its own file.
+    pub fn save_routine(&mut self) {
+        let saved: Result<Vec<String>> = self
+            .routine_paths
+            .iter()
+            .enumerate()
+            .map(|(source, path)| {
+                let tasks: Vec<Task> = self
+                    .tasks
+                    .tasks
+                    .iter()
+                    .filter(|task| task.source == source && !task.session_only && !task.is_break)
+                    .cloned()
+                    .collect();
+                routine::parse::write_csv(path, &tasks)?;
+                Ok(path.display().to_string())
+            })
+            .collect();
+        let message = match saved {
+            Ok(paths) if paths.is_empty() => "Nowhere to save the routine to.".to_string(),
+            Ok(paths) => format!("Saved to {}", paths.join(", ")),
+            Err(e) => format!("Failed to save: {e}"),
+        };
+        if let Some(summary) = &mut self.summary {
+            summary.saved = Some(message);
+        }
+    }
+
+    /// How long the current pause has lasted, if paused.
+    pub fn get_current_pause_duration(&self) -> Option<Duration> {
+        self.pauses
+            .current()
+            .map(|pause| pause.duration(Local::now()))
+    }
+
+    pub fn get_total_pause_time(&self) -> Duration {
+        self.pauses.total(Local::now())
+    }
+
+    /// How far the current pause has gone over the pause budget, if it has. Zero if it's
+    /// over by an unknown amount.
+    pub fn pause_over_budget(&self) -> Option<Duration> {
+        self.pause_over_budget_at(Local::now())
+    }
+
+    /// How far over the pause budget the pausing is at `now`, if it is. The budget is the one
+    /// for the routine that was running when pausing.
+    fn pause_over_budget_at(&self, now: DateTime<Local>) -> Option<Duration> {
+        let budget = self
+            .pauses
+            .current()
+            .and_then(|pause| self.routine_paths.get(pause.source))
+            .map_or(self.config.pause_budget, |path| {
+                self.config.pause_budget_for(path)
+            });
+        self.pauses.over_budget(budget, self.tasks.slack(now), now)
     }
 
     pub fn get_time_elapsed(&self) -> Duration {
         self.tasks.elapsed()
     }
 
+    /// How far through the routine it is, from 0 to 1, measured as the config says.
     pub fn get_percentage_elapsed(&self) -> f64 {
-        // TODO should the percentage bar be configurable? Another option could be
-        // number of tasks completed, which would make the effort of task
-        // switching more recognized. The value of leaving it this way even then is honing
-        // your time understanding skills.
-        self.tasks
-            .completed_originals()
-            .div_duration_f64(self.tasks.total_originals())
+        let progress = &self.config.progress;
+        self.tasks.progress(progress.metric, progress.count_skipped)
     }
 
     /// Set `should_quit` to `true` to quit the application.
     pub fn quit(&mut self) {
+        self.end_pause("");
         self.logger.finish();
+        if let Some(dir) = self.status_dir() {
+            status::clear(&dir);
+        }
         self.should_quit = true;
     }
 
@@ -155,107 +454,468 @@ impl App {
         self.menu_focus = Mode::Typing(Menu::InsertTask);
     }
 
+    /// Opens the selected task in the text field, written the same way a new task would be.
+    pub fn edit_task_start(&mut self) {
+        let Some(task) = self.tasks.get_nth(self.task_widget_state.selected()) else {
+            return;
+        };
+        self.text_input = TextField::with_text(task::format_task(task));
+        self.menu_focus = Mode::Typing(Menu::EditTask);
+    }
+
+    /// Adds another go at the selected task right after it, with the same original duration.
+    /// It's only for this session, so saving the routine leaves it out.
+    pub fn repeat_task(&mut self) {
+        let i = self.task_widget_state.selected();
+        let (Some(index), Some(task)) = (i, self.tasks.get_nth(i)) else {
+            return;
+        };
+        let mut copy = task.fresh_copy();
+        copy.session_only = true;
+        self.insert_task(index + 1, copy);
+    }
+
+    /// Asks for confirmation before deleting the selected task.
+    pub fn delete_task_start(&mut self) {
+        if self
+            .tasks
+            .get_nth(self.task_widget_state.selected())
+            .is_some()
+        {
+            self.menu_focus = Mode::Confirm(Confirmation::DeleteTask);
+        }
+    }
+
+    fn delete_task(&mut self) {
+        let before = self.task_widget_state;
+        let Some(index) = before.selected() else {
+            return;
+        };
+        if let Some(task) = self.tasks.remove(Some(index)) {
+            self.task_widget_state.delete_current();
+            self.logger.log(LogElement::deleted(&task));
+            self.history.record(
+                Command::Delete { index, task },
+                before,
+                self.task_widget_state,
+            );
+        }
+    }
+
+    /// Ticks, or unticks, item `item` of the selected task's checklist.
+    pub fn toggle_checklist_item(&mut self, item: usize) {
+        let i = self.task_widget_state.selected();
+        // ticking doesn't change any durations, so there's nothing to flex
+        let Some(task) = i.and_then(|i| self.tasks.tasks.get_mut(i)) else {
+            return;
+        };
+        if let Some(ticked) = task.toggle_checklist_item(item).cloned() {
+            self.logger.log(LogElement::checklist(task, &ticked));
+        }
+    }
+
+    pub fn move_task_up(&mut self) {
+        if let Some(i) = self.task_widget_state.selected() {
+            if i > 0 {
+                self.move_task(i, i - 1);
+            }
+        }
+    }
+
+    pub fn move_task_down(&mut self) {
+        if let Some(i) = self.task_widget_state.selected() {
+            self.move_task(i, i + 1);
+        }
+    }
+
+    /// Moves a task and keeps it selected.
+    fn move_task(&mut self, from: usize, to: usize) {
+        if self.tasks.move_task(from, to).is_err() {
+            return;
+        }
+        let before = self.task_widget_state;
+        let _ = self.task_widget_state.select(Some(to));
+        if let Some(task) = self.tasks.get_nth(Some(to)) {
+            self.logger.log(LogElement::moved(task, from, to));
+            let name = task.name.clone();
+            self.history.record(
+                Command::Move { name, from, to },
+                before,
+                self.task_widget_state,
+            );
+        }
+    }
+
     pub fn pause(&mut self) {
+        let task = self.tasks.get_nth(self.task_widget_state.selected());
+        let name = task.map(|task| task.name.clone());
+        let source = task.map_or(0, |task| task.source);
+        self.pauses.start(Local::now(), name, source);
+        self.run_hook(HookEvent::Pause, self.task_widget_state.selected(), None);
         self.task_widget_state.pause();
         self.menu_focus = Mode::Typing(Menu::Pause);
     }
 
-    fn append_task_submit(&mut self) {
-        let name = self.text_input.lines()[0].clone();
-        let task = task::parse_new(&name);
-        self.task_widget_state.append_item();
-        self.tasks.push(task);
+    /// What's been typed in the text field.
+    fn text_input_line(&self) -> String {
+        self.text_input.text().to_string()
     }
 
-    fn insert_task_submit(&mut self) {
-        let name = self.text_input.lines()[0].clone();
-        // TODO fix ownership of name
-        let task = task::parse_new(&name);
-        self.task_widget_state.append_item();
-        let i = self.task_widget_state.selected().unwrap_or(0) + 1;
-        self.tasks.insert(i, task);
+    /// The task typed for `menu`, and where it goes, or everything wrong with it.
+    fn typed_task(&self, menu: Menu) -> Result<(Task, usize), Vec<String>> {
+        let unit = self.config.default_duration_unit;
+        let new = match menu {
+            Menu::EditTask => task::parse_edit(self.text_input.text(), unit),
+            _ => task::parse_new(self.text_input.text(), unit),
+        };
+        let len = self.tasks.tasks.len();
+        match menu {
+            Menu::InsertTask => {
+                let after_selected = self
+                    .task_widget_state
+                    .selected()
+                    .map_or(0, |i| i + 1)
+                    .min(len);
+                self.place(new, after_selected)
+            }
+            _ => self.place(new, len),
+        }
     }
 
-    fn unpause(&mut self) {
-        // TODO pausing should log the
-        // duration of the pause,
-        // the fact that it was a pause,
-        // and the message.
-        let message = self.text_input.lines()[0].clone();
-        self.logger.log_comment(&message, Local::now());
+    /// Where `new` goes: where it says, or else `default`.
+    fn place(&self, new: NewTask, default: usize) -> Result<(Task, usize), Vec<String>> {
+        let mut problems = new.problems;
+        let index = match new.position {
+            None => default,
+            Some(Position::End) => self.tasks.tasks.len(),
+            Some(Position::After(name)) => {
+                let found = self
+                    .tasks
+                    .tasks
+                    .iter()
+                    .position(|task| task.name.eq_ignore_ascii_case(&name));
+                if found.is_none() {
+                    problems.push(format!("no task named \"{name}\""));
+                }
+                found.map_or(default, |i| i + 1)
+            }
+        };
+        if problems.is_empty() {
+            Ok((new.task, index))
+        } else {
+            Err(problems)
+        }
+    }
+
+    /// What's wrong with the task being typed, to show before it's submitted.
+    pub fn typing_problems(&self) -> Vec<String> {
+        match self.menu_focus {
+            Mode::Typing(Menu::Pause) => vec![],
+            _ if self.text_input.text().trim().is_empty() => vec![],
+            Mode::Typing(menu) => self.typed_task(menu).err().unwrap_or_default(),
+            _ => vec![],
+        }
+    }
+
+    /// Tasks that could be meant by what's being typed, while it's still only a name.
+    pub fn suggestions(&self) -> Vec<Suggestion> {
+        if !matches!(
+            self.menu_focus,
+            Mode::Typing(Menu::AppendTask | Menu::InsertTask)
+        ) {
+            return vec![];
+        }
+        let typed = self.text_input.text();
+        let new = task::parse_new(typed, self.config.default_duration_unit);
+        let words: Vec<&str> = typed.split_whitespace().collect();
+        if new.has_extras() || new.task.name != words.join(" ") {
+            return vec![];
+        }
+        suggest::suggest(typed, &self.tasks, &self.task_history)
+    }
+
+    pub fn next_suggestion(&mut self) {
+        if self.suggestion + 1 < self.suggestions().len() {
+            self.suggestion += 1;
+        }
+    }
+
+    pub fn prev_suggestion(&mut self) {
+        self.suggestion = self.suggestion.saturating_sub(1);
+    }
+
+    /// Replaces what's typed with the selected suggestion and its typical duration.
+    pub fn accept_suggestion(&mut self) {
+        let Some(suggestion) = self.suggestions().into_iter().nth(self.suggestion) else {
+            return;
+        };
+        let text = match suggestion.typical {
+            Some(typical) => format!(
+                "{} {}",
+                suggestion.name,
+                task::duration::format_duration_compact(typical.as_secs())
+            ),
+            None => suggestion.name,
+        };
+        self.text_input = TextField::with_text(text);
+        self.suggestion = 0;
+    }
+
+    /// Adds a task written like one typed in, at the end unless it says otherwise.
+    ///
+    /// # Errors
+    ///
+    /// Will return everything wrong with `line`, without adding anything, if it can't all be
+    /// understood.
+    pub fn add_task(&mut self, line: &str) -> Result<(), Vec<String>> {
+        let new = task::parse_new(line, self.config.default_duration_unit);
+        let (task, index) = self.place(new, self.tasks.tasks.len())?;
+        self.insert_task(index, task);
+        Ok(())
+    }
+
+    /// Adds a task at `index`, keeping the same task selected.
+    pub fn insert_task(&mut self, index: usize, task: Task) {
+        let before = self.task_widget_state;
+        let task = self.put_task(index, task);
+        self.logger.log(LogElement::added(&task, index));
+        self.history.record(
+            Command::Insert { index, task },
+            before,
+            self.task_widget_state,
+        );
+    }
+
+    /// Puts `task` in at `index`, in the routine and repeated block it goes inside of, and
+    /// returns it as it was put in.
+    fn put_task(&mut self, index: usize, mut task: Task) -> Task {
+        // a new task joins the routine of the task it goes after
+        let neighbour = index
+            .checked_sub(1)
+            .or(Some(0))
+            .and_then(|i| self.tasks.tasks.get(i));
+        if let Some(neighbour) = neighbour {
+            task.source = neighbour.source;
+        }
+        // and any repeated block it goes inside of
+        let before = index.checked_sub(1).and_then(|i| self.tasks.tasks.get(i));
+        if let (Some(before), Some(after)) = (before, self.tasks.tasks.get(index)) {
+            task.repeat = repeat::shared(&before.repeat, &after.repeat);
+        }
+        self.task_widget_state.insert_item(index);
+        self.tasks.insert(index, task.clone());
+        task
+    }
+
+    fn edit_task(&mut self, edited: Task) {
+        let i = self.task_widget_state.selected();
+        let (Some(index), Some(task)) = (i, self.tasks.get_nth(i)) else {
+            return;
+        };
+        let (old_name, old_duration) = (task.name.clone(), task.original_duration);
+        if self
+            .tasks
+            .edit(i, &edited.name, edited.original_duration)
+            .is_err()
+        {
+            return;
+        }
+        if let Some(task) = self.tasks.get_nth(i) {
+            self.logger
+                .log(LogElement::edited(task, &old_name, old_duration));
+        }
+        self.history.record(
+            Command::Edit {
+                index,
+                before: (old_name, old_duration),
+                after: (edited.name, edited.original_duration),
+            },
+            self.task_widget_state,
+            self.task_widget_state,
+        );
+    }
+
+    /// Ends the pause, if paused, and logs it with `reason`.
+    fn end_pause(&mut self, reason: &str) {
+        let Some(pause) = self.pauses.end(Local::now(), reason) else {
+            return;
+        };
+        self.logger.log(LogElement::paused(pause));
         self.task_widget_state.unpause();
+        self.run_hook(
+            HookEvent::Resume,
+            self.task_widget_state.selected(),
+            Some(reason),
+        );
+    }
+
+    fn unpause(&mut self) {
+        let message = self.text_input_line();
+        self.end_pause(&message);
+    }
+
+    /// Unpauses with `reason` for the log, leaving whatever was typed while paused.
+    pub fn resume(&mut self, reason: &str) {
+        self.text_input = TextField::default();
+        self.menu_focus = Mode::Navigation;
+        self.end_pause(reason);
     }
 
     pub fn cancel_typing(&mut self, menu: Menu) {
-        self.text_input = TextArea::default();
+        self.text_input = TextField::default();
+        self.suggestion = 0;
         self.menu_focus = Mode::Navigation;
         if menu == Menu::Pause {
-            // TODO duplicates work. refactor?
-            self.task_widget_state.unpause();
+            self.end_pause("");
         }
     }
 
+    /// Submits what was typed. A task with problems isn't submitted, and the problems stay
+    /// shown until they're fixed or typing is cancelled.
     pub fn submit_typing(&mut self, menu: Menu) {
         match menu {
-            Menu::AppendTask => self.append_task_submit(),
-            Menu::InsertTask => self.insert_task_submit(),
             Menu::Pause => self.unpause(),
+            _ if self.text_input.text().trim().is_empty() => (),
+            Menu::AppendTask | Menu::InsertTask | Menu::EditTask => match self.typed_task(menu) {
+                Err(_) => return,
+                Ok((task, _)) if menu == Menu::EditTask => self.edit_task(task),
+                Ok((task, index)) => self.insert_task(index, task),
+            },
         }
         self.cancel_typing(menu);
     }
 
+    pub fn confirm(&mut self, confirmation: Confirmation) {
+        match confirmation {
+            Confirmation::DeleteTask => self.delete_task(),
+        }
+        self.cancel_confirm();
+    }
+
+    pub fn cancel_confirm(&mut self) {
+        self.menu_focus = Mode::Navigation;
+    }
+
     pub fn toggle_debug(&mut self) {
         self.debug = !self.debug;
     }
 
+    pub fn toggle_focus(&mut self) {
+        self.focus = !self.focus;
+    }
+
     pub fn toggle_help(&mut self) {
         self.help_menu = !self.help_menu;
+        self.help_scroll = 0;
+    }
+
+    /// Scrolls the help menu down, until its last line is at the bottom.
+    pub fn scroll_help_down(&mut self) {
+        let lines = crate::ui::help_line_count(&self.menu_focus);
+        let max_scroll = lines.saturating_sub(self.help_height.into());
+        if usize::from(self.help_scroll) < max_scroll {
+            self.help_scroll += 1;
+        }
+    }
+
+    pub fn scroll_help_up(&mut self) {
+        self.help_scroll = self.help_scroll.saturating_sub(1);
     }
 
     pub fn attempt_toggle(&mut self) {
-        let i = self.task_widget_state.selected();
-        match self.tasks.toggle(i) {
-            Ok(CompletionStatus::Done) => {
-                let task = self
-                    .tasks
-                    .get_nth(i)
-                    .expect("this should always exist here");
+        let before = self.task_widget_state;
+        let i = before.selected();
+        let (Some(index), Some(task)) = (i, self.tasks.get_nth(i)) else {
+            return;
+        };
+        let old_status = task.status;
+        let Ok(status) = self.tasks.toggle(i) else {
+            return;
+        };
+        let Some(task) = self.tasks.get_nth(i) else {
+            return;
+        };
+        let name = task.name.clone();
+        match status {
+            CompletionStatus::Done => {
                 self.logger.log(LogElement::completed(task));
+                self.run_hook(HookEvent::TaskDone, i, None);
                 self.bouncing_next_task();
             }
-            Ok(CompletionStatus::NotYet) => {
-                let task = self
-                    .tasks
-                    .get_nth(self.task_widget_state.selected())
-                    .expect("this should always exist here");
-                self.logger.log(LogElement::uncompleted(task));
-            }
-            Err(_) => (),
-            Ok(CompletionStatus::Skipped) => panic!("this should never happen?"),
+            CompletionStatus::NotYet => self.logger.log(LogElement::uncompleted(task)),
+            // toggling never skips a task
+            CompletionStatus::Skipped => (),
         };
+        self.record_status(index, name, old_status, status, before);
     }
 
     pub fn attempt_skip(&mut self) {
-        let i = self.task_widget_state.selected();
-        match self.tasks.skip(i) {
-            Ok(CompletionStatus::Skipped) => {
-                let task = self
-                    .tasks
-                    .get_nth(i)
-                    .expect("this should always exist here");
+        let before = self.task_widget_state;
+        let i = before.selected();
+        let (Some(index), Some(task)) = (i, self.tasks.get_nth(i)) else {
+            return;
+        };
+        let old_status = task.status;
+        let Ok(status) = self.tasks.skip(i) else {
+            return;
+        };
+        let Some(task) = self.tasks.get_nth(i) else {
+            return;
+        };
+        let name = task.name.clone();
+        match status {
+            CompletionStatus::Skipped => {
                 self.logger.log(LogElement::skipped(task));
+                self.run_hook(HookEvent::TaskSkipped, i, None);
                 self.bouncing_next_task();
             }
-            Ok(CompletionStatus::NotYet) => {
-                let task = self
-                    .tasks
-                    .get_nth(self.task_widget_state.selected())
-                    .expect("this should always exist here");
-                self.logger.log(LogElement::unskipped(task));
-            }
-            Err(_) => (),
-            Ok(CompletionStatus::Done) => panic!("this should never happen?"),
+            CompletionStatus::NotYet => self.logger.log(LogElement::unskipped(task)),
+            // skipping never completes a task
+            CompletionStatus::Done => (),
         };
+        self.record_status(index, name, old_status, status, before);
+    }
+
+    fn record_status(
+        &mut self,
+        index: usize,
+        name: String,
+        before: CompletionStatus,
+        after: CompletionStatus,
+        pointer_before: ListPointer,
+    ) {
+        self.history.record(
+            Command::Status {
+                index,
+                name,
+                before,
+                after,
+            },
+            pointer_before,
+            self.task_widget_state,
+        );
+    }
+
+    /// Reverses the most recent change to the routine.
+    pub fn undo(&mut self) {
+        if let Some(command) = self
+            .history
+            .undo(&mut self.tasks, &mut self.task_widget_state)
+        {
+            let (name, action) = command.describe();
+            self.logger.log(LogElement::undone(name, action));
+        }
+    }
+
+    /// Does the most recently undone change again.
+    pub fn redo(&mut self) {
+        if let Some(command) = self
+            .history
+            .redo(&mut self.tasks, &mut self.task_widget_state)
+        {
+            let (name, action) = command.describe();
+            self.logger.log(LogElement::redone(name, action));
+        }
     }
 
     pub fn next_task(&mut self) {
@@ -291,21 +951,464 @@ impl App {
     }
 }
 
+/// Finds the next time the clock reads `deadline`: today, unless that has already passed.
+///
+/// # Errors
+///
+/// Will return an error if the deadline falls in a gap in the local clock, like during a DST
+/// change.
+pub fn next_deadline(now: DateTime<Local>, deadline: NaiveTime) -> Result<DateTime<Local>> {
+    // Around a DST change where a time happens twice, go with the first one. It's
+    // better to be early.
+    let today = now
+        .with_time(deadline)
+        .earliest()
+        .ok_or_eyre("Deadline does not exist today on the local clock.")?;
+    if today >= now {
+        return Ok(today);
+    }
+    now.checked_add_days(Days::new(1))
+        .and_then(|tomorrow| tomorrow.with_time(deadline).earliest())
+        .ok_or_eyre("Deadline does not exist tomorrow on the local clock.")
+}
+
 #[derive(Debug)]
 pub enum Mode {
     Navigation,
     Typing(Menu),
+    Confirm(Confirmation),
+    /// Every task is done or skipped.
+    Summary,
 }
 
 #[derive(PartialEq, Debug, Copy, Clone)]
 pub enum Menu {
     AppendTask,
     InsertTask,
+    EditTask,
     Pause,
 }
 
+/// Actions that have to be confirmed before they happen.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum Confirmation {
+    DeleteTask,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    // TODO
+
+    use crate::config::DurationSetting;
+    use crate::routine::task::{duration::DurationUnit, ChecklistItem};
+    use std::collections::HashMap;
+
+    fn test_app() -> App {
+        let tasks = Routine::with_tasks(vec![
+            Task::new("a", 60),
+            Task::new("b", 60),
+            Task::new("c", 60),
+        ]);
+        App::with_routine(
+            Config::default(),
+            tasks,
+            RoutineLogger::with_writer(std::io::sink()),
+        )
+    }
+
+    fn names(app: &App) -> Vec<&str> {
+        app.tasks.tasks.iter().map(|t| t.name.as_str()).collect()
+    }
+
+    #[test]
+    fn delete_needs_confirmation() {
+        let mut app = test_app();
+
+        app.delete_task_start();
+        app.cancel_confirm();
+
+        assert_eq!(names(&app), ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn delete_last_keeps_pointer_in_list() {
+        let mut app = test_app();
+        app.task_widget_state.select(Some(2)).unwrap();
+
+        app.delete_task_start();
+        app.confirm(Confirmation::DeleteTask);
+
+        assert_eq!(names(&app), ["a", "b"]);
+        assert_eq!(app.get_current_task_name(), Some("b"));
+        assert_eq!(app.task_widget_state.length(), 2);
+    }
+
+    #[test]
+    fn moved_task_stays_selected() {
+        let mut app = test_app();
+
+        app.move_task_down();
+        app.move_task_down();
+        app.move_task_down();
+
+        assert_eq!(names(&app), ["b", "c", "a"]);
+        assert_eq!(app.get_current_task_name(), Some("a"));
+    }
+
+    #[test]
+    fn undo_complete_moves_back() {
+        let mut app = test_app();
+        app.attempt_toggle();
+
+        app.undo();
+
+        assert_eq!(app.tasks.tasks[0].status, CompletionStatus::NotYet);
+        assert_eq!(app.get_current_task_name(), Some("a"));
+    }
+
+    #[test]
+    fn undo_and_redo_delete() {
+        let mut app = test_app();
+        app.task_widget_state.select(Some(1)).unwrap();
+        app.delete_task_start();
+        app.confirm(Confirmation::DeleteTask);
+
+        app.undo();
+        assert_eq!(names(&app), ["a", "b", "c"]);
+        assert_eq!(app.get_current_task_name(), Some("b"));
+
+        app.redo();
+        assert_eq!(names(&app), ["a", "c"]);
+        assert_eq!(app.get_current_task_name(), Some("c"));
+    }
+
+    #[test]
+    fn undo_append() {
+        let mut app = test_app();
+        app.append_task_start();
+        app.text_input.insert_str("d 1m");
+        app.submit_typing(Menu::AppendTask);
+
+        app.undo();
+
+        assert_eq!(names(&app), ["a", "b", "c"]);
+        assert_eq!(app.task_widget_state.length(), 3);
+    }
+
+    #[test]
+    fn append_after_named_task() {
+        let mut app = test_app();
+        app.append_task_start();
+        app.text_input.insert_str("d 1m @after A");
+        app.submit_typing(Menu::AppendTask);
+
+        assert_eq!(names(&app), ["a", "d", "b", "c"]);
+        assert_eq!(app.get_current_task_name(), Some("a"));
+    }
+
+    #[test]
+    fn repeat_a_task_once_more() {
+        let mut app = test_app();
+        app.tasks.tasks[0].elapsed = Duration::from_secs(30);
+        app.repeat_task();
+
+        assert_eq!(names(&app), ["a", "a", "b", "c"]);
+        assert_eq!(app.tasks.tasks[1].elapsed, Duration::ZERO);
+        assert_eq!(
+            app.tasks.tasks[1].original_duration,
+            Duration::from_secs(60)
+        );
+        assert_eq!(app.get_current_task_name(), Some("a"));
+        app.undo();
+        assert_eq!(names(&app), ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn save_writes_repeat_blocks_back_once() {
+        let path =
+            std::env::temp_dir().join(format!("accordion-save-repeat-{}", std::process::id()));
+        let routine = "task,duration\nrepeat 2,\npushups,1m\nrest,30s\nend repeat,\nstretch,5m\n";
+        std::fs::write(&path, routine).unwrap();
+        let tasks = routine::parse::read_csv(&path, DurationUnit::Minutes).unwrap();
+        let mut app = App::with_routine(
+            Config::default(),
+            Routine::with_tasks(tasks),
+            RoutineLogger::with_writer(std::io::sink()),
+        );
+        app.routine_paths = vec![path.clone()];
+        // another go is only for now, but a task added inside the first copy joins the block
+        app.repeat_task();
+        app.add_task("squat 1m @after pushups (1/2)").unwrap();
+        app.save_routine();
+
+        let saved = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            saved,
+            "task,duration\nrepeat 2,\npushups,1m\nsquat,1m\nrest,30s\nend repeat,\nstretch,5m\n"
+        );
+    }
+
+    #[test]
+    fn break_after_enough_time_on_tasks() {
+        let mut app = test_app();
+        app.config.breaks.every = Some(DurationSetting(Duration::from_secs(90)));
+        app.config.breaks.length = DurationSetting(Duration::from_secs(30));
+
+        app.track_breaks(Duration::from_secs(60));
+        assert_eq!(names(&app), ["a", "b", "c"]);
+        app.track_breaks(Duration::from_secs(30));
+        assert_eq!(names(&app), ["a", "Break", "b", "c"]);
+        assert!(app.tasks.tasks[1].is_break);
+        assert_eq!(
+            app.tasks.tasks[1].original_duration,
+            Duration::from_secs(30)
+        );
+
+        // no second break while one is still to come
+        app.track_breaks(Duration::from_secs(90));
+        assert_eq!(names(&app), ["a", "Break", "b", "c"]);
+    }
+
+    #[test]
+    fn breaks_are_not_undone_or_saved() {
+        let path = std::env::temp_dir().join(format!("accordion-break-{}", std::process::id()));
+        let mut app = test_app();
+        app.routine_paths = vec![path.clone()];
+        app.config.breaks.every = Some(DurationSetting(Duration::from_secs(60)));
+        app.add_task("d 1m @after a").unwrap();
+        app.track_breaks(Duration::from_secs(60));
+        assert_eq!(names(&app), ["a", "Break", "d", "b", "c"]);
+
+        app.undo();
+        assert_eq!(names(&app), ["a", "Break", "b", "c"]);
+        app.save_routine();
+        let saved = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(saved, "task,duration\na,1m\nb,1m\nc,1m\n");
+    }
+
+    #[test]
+    fn chained_routines_save_separately() {
+        let dir = std::env::temp_dir().join(format!("accordion-chain-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut app = test_app();
+        app.routine_paths = vec![dir.join("first.csv"), dir.join("second.csv")];
+        app.tasks.tasks[2].source = 1;
+        // added after b, so it joins the first routine
+        app.next_task();
+        app.add_task("d 1m @after b").unwrap();
+        app.save_routine();
+
+        let first = std::fs::read_to_string(dir.join("first.csv")).unwrap();
+        let second = std::fs::read_to_string(dir.join("second.csv")).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(first, "task,duration\na,1m\nb,1m\nd,1m\n");
+        assert_eq!(second, "task,duration\nc,1m\n");
+    }
+
+    #[test]
+    fn problems_keep_the_prompt_open() {
+        let mut app = test_app();
+        app.insert_task_start();
+        app.text_input.insert_str("d 1m !urgent @after z");
+
+        assert_eq!(
+            app.typing_problems(),
+            [
+                "unknown flag \"!urgent\"; use !rigid or ?optional",
+                "no task named \"z\""
+            ]
+        );
+        app.submit_typing(Menu::InsertTask);
+
+        assert_eq!(names(&app), ["a", "b", "c"]);
+        assert!(matches!(app.menu_focus, Mode::Typing(Menu::InsertTask)));
+    }
+
+    #[test]
+    fn tab_accepts_suggestion() {
+        let mut app = test_app();
+        app.tasks.tasks[1].name = "feed cat".to_string();
+        app.task_history.added("feed dog");
+        app.append_task_start();
+        app.text_input.insert_str("fee");
+
+        app.next_suggestion();
+        app.accept_suggestion();
+
+        assert_eq!(app.text_input.text(), "feed dog");
+        app.text_input.insert_str(" 2m");
+        assert!(app.suggestions().is_empty());
+    }
+
+    #[test]
+    fn tick_checklist_items() {
+        let mut app = test_app();
+        app.tasks.tasks[0].checklist =
+            vec![ChecklistItem::new("laptop"), ChecklistItem::new("badge")];
+
+        app.toggle_checklist_item(1);
+        app.toggle_checklist_item(5);
+
+        let done: Vec<bool> = app.tasks.tasks[0]
+            .checklist
+            .iter()
+            .map(|i| i.done)
+            .collect();
+        assert_eq!(done, [false, true]);
+        assert_eq!(app.tasks.tasks[0].duration, Duration::from_secs(60));
+    }
+
+    #[test]
+    fn undo_edit() {
+        let mut app = test_app();
+        app.edit_task_start();
+        app.text_input = TextField::with_text("z 2m");
+        app.submit_typing(Menu::EditTask);
+
+        app.undo();
+
+        assert_eq!(app.tasks.tasks[0].name, "a");
+        assert_eq!(
+            app.tasks.tasks[0].original_duration,
+            Duration::from_secs(60)
+        );
+    }
+
+    #[test]
+    fn edit_names_with_markers() {
+        let mut app = test_app();
+        app.tasks.tasks[0].name = "#1 fan -- dust".to_string();
+        app.edit_task_start();
+        assert!(app.typing_problems().is_empty());
+
+        app.text_input = TextField::with_text("#1 fan -- dust @ home 2m");
+        app.submit_typing(Menu::EditTask);
+
+        assert_eq!(app.tasks.tasks[0].name, "#1 fan -- dust @ home");
+        assert_eq!(
+            app.tasks.tasks[0].original_duration,
+            Duration::from_secs(120)
+        );
+    }
+
+    #[test]
+    fn pause_remembers_task_and_reason() {
+        let mut app = test_app();
+        app.pause();
+        app.text_input.insert_str("phone call");
+
+        app.submit_typing(Menu::Pause);
+
+        let pause = &app.pauses.finished()[0];
+        assert_eq!(pause.task.as_deref(), Some("a"));
+        assert_eq!(pause.reason, "phone call");
+        assert_eq!(app.get_current_task_name(), Some("a"));
+    }
+
+    #[test]
+    fn cancelled_pause_is_still_recorded() {
+        let mut app = test_app();
+        app.pause();
+
+        app.cancel_typing(Menu::Pause);
+
+        assert_eq!(app.pauses.finished().len(), 1);
+        assert!(app.get_current_pause_duration().is_none());
+    }
+
+    #[test]
+    fn zero_pause_budget_is_exceeded_right_away() {
+        let mut app = test_app();
+        app.config.pause_budget = Some(config::PauseBudget::Fixed(Duration::ZERO));
+        app.pause();
+        let later = Local::now() + chrono::TimeDelta::seconds(1);
+
+        assert!(app.pause_over_budget_at(later).is_some());
+    }
+
+    #[test]
+    fn chained_routines_have_their_own_pause_budgets() {
+        let mut app = test_app();
+        app.routine_paths = vec![
+            PathBuf::from("kids/bedtime.csv"),
+            PathBuf::from("wind_down.csv"),
+        ];
+        app.tasks.tasks[2].source = 1;
+        app.config.pause_budget = Some(config::PauseBudget::Fixed(Duration::ZERO));
+        app.config.pause_budgets = HashMap::from([(
+            "bedtime.csv".to_string(),
+            config::PauseBudget::Fixed(Duration::from_secs(60)),
+        )]);
+        let later = Local::now() + chrono::TimeDelta::seconds(30);
+
+        app.pause();
+        assert!(app.pause_over_budget_at(later).is_none());
+        app.submit_typing(Menu::Pause);
+
+        app.task_widget_state.select(Some(2)).unwrap();
+        app.pause();
+        assert!(app.pause_over_budget_at(later).is_some());
+    }
+
+    #[test]
+    fn summary_after_last_task() {
+        let mut app = test_app();
+        app.attempt_toggle();
+        app.attempt_skip();
+        app.tick();
+        assert!(matches!(app.menu_focus, Mode::Navigation));
+
+        app.attempt_toggle();
+        app.tick();
+
+        assert!(matches!(app.menu_focus, Mode::Summary));
+        assert_eq!(app.summary.as_ref().unwrap().skipped(), 1);
+    }
+
+    #[test]
+    fn summary_reopens_after_going_back() {
+        let mut app = test_app();
+        app.reopen_summary();
+        assert!(matches!(app.menu_focus, Mode::Navigation));
+
+        for _ in 0..app.tasks.tasks.len() {
+            app.attempt_toggle();
+        }
+        app.tick();
+        app.back_to_routine();
+        app.tick();
+        assert!(matches!(app.menu_focus, Mode::Navigation));
+
+        app.reopen_summary();
+        assert!(matches!(app.menu_focus, Mode::Summary));
+    }
+
+    #[test]
+    fn summary_goes_away_after_undo() {
+        let mut app = test_app();
+        for _ in 0..app.tasks.tasks.len() {
+            app.attempt_toggle();
+        }
+        app.tick();
+        app.back_to_routine();
+
+        app.undo();
+        app.tick();
+
+        assert!(app.summary.is_none());
+    }
+
+    #[test]
+    fn move_first_up_does_nothing() {
+        let mut app = test_app();
+
+        app.move_task_up();
+
+        assert_eq!(names(&app), ["a", "b", "c"]);
+    }
 }
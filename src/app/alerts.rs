@@ -0,0 +1,195 @@
+// Alerts for when a task runs out of time or the deadline gets hard to make, so the
+// routine can be followed without watching the screen.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+use std::{env, io, thread};
+
+use crate::config::Alerts;
+use crate::routine::{CompletionStatus, Task};
+
+const ONE_MINUTE: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Alert {
+    TimeUp(String),
+    OneMinuteLeft(String),
+    DeadlineAtRisk,
+}
+
+impl Alert {
+    /// Whether the alert is turned on in the config.
+    pub fn enabled(&self, config: &Alerts) -> bool {
+        match self {
+            Self::TimeUp(_) => config.time_up,
+            Self::OneMinuteLeft(_) => config.one_minute_left,
+            Self::DeadlineAtRisk => config.deadline_at_risk,
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            Self::TimeUp(name) => format!("Time's up for {name}"),
+            Self::OneMinuteLeft(name) => format!("1 minute left for {name}"),
+            Self::DeadlineAtRisk => "The deadline is at risk".to_string(),
+        }
+    }
+}
+
+/// Remembers enough of the previous tick to give each alert once, when its threshold is
+/// crossed, rather than on every tick past it.
+#[derive(Debug, Default)]
+pub struct AlertTracker {
+    /// Index, name and remaining time of the task selected last tick.
+    last: Option<(usize, String, Duration)>,
+    at_risk: bool,
+}
+
+impl AlertTracker {
+    /// Returns the alerts due this tick, whether or not they're turned on. `compression` is
+    /// from [`Routine::compression`](crate::routine::Routine::compression), and the
+    /// deadline is at risk below `risk`.
+    pub fn check(
+        &mut self,
+        risk: f64,
+        selected: Option<(usize, &Task)>,
+        compression: Option<f64>,
+    ) -> Vec<Alert> {
+        let mut alerts = vec![];
+        let current = selected
+            .filter(|(_, task)| task.status == CompletionStatus::NotYet)
+            .map(|(index, task)| (index, task.name.clone(), task.remaining()));
+        if let (Some((index, name, before)), Some((now_index, now_name, now))) =
+            (&self.last, &current)
+        {
+            // only compare with the same task, so switching tasks doesn't count as crossing
+            if index == now_index && name == now_name {
+                if !before.is_zero() && now.is_zero() {
+                    alerts.push(Alert::TimeUp(name.clone()));
+                } else if *before > ONE_MINUTE && *now <= ONE_MINUTE {
+                    alerts.push(Alert::OneMinuteLeft(name.clone()));
+                }
+            }
+        }
+        self.last = current;
+
+        let at_risk = compression.is_some_and(|c| c < risk);
+        if at_risk && !self.at_risk {
+            alerts.push(Alert::DeadlineAtRisk);
+        }
+        self.at_risk = at_risk;
+        alerts
+    }
+}
+
+/// Sends the alert through every output that's configured. Commands are started in the
+/// background, so this returns right away.
+pub fn send(config: &Alerts, alert: &Alert) {
+    if config.bell {
+        // stderr is where the interface is drawn, so the bell goes to the terminal
+        let mut stderr = io::stderr();
+        if let Err(e) = stderr.write_all(b"\x07").and_then(|()| stderr.flush()) {
+            cli_log::warn!("Couldn't ring the bell: {e}");
+        }
+    }
+    if let Some(program) = find_program(&config.desktop_command) {
+        let mut command = Command::new(program);
+        command.args(["Accordion Task", &alert.message()]);
+        spawn(command);
+    }
+    if let Some(sound) = &config.sound_command {
+        let mut command = Command::new("sh");
+        command.args(["-c", sound]);
+        spawn(command);
+    }
+}
+
+/// Starts the command with no input or output and waits for it on another thread.
+pub fn spawn(mut command: Command) {
+    let child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+    match child {
+        Ok(mut child) => {
+            thread::spawn(move || child.wait());
+        }
+        Err(e) => cli_log::warn!("Couldn't run {:?}: {e}", command.get_program()),
+    }
+}
+
+/// Where `name` is, if it's a path to a file or a program on `PATH`.
+fn find_program(name: &str) -> Option<PathBuf> {
+    if name.is_empty() {
+        return None;
+    }
+    if name.contains('/') {
+        return Path::new(name).is_file().then(|| name.into());
+    }
+    env::split_paths(&env::var_os("PATH")?)
+        .map(|dir| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RISK: f64 = 0.5;
+
+    fn task(remaining: u64) -> Task {
+        let mut task = Task::new("a", 120);
+        task.elapse(Duration::from_secs(120 - remaining));
+        task
+    }
+
+    #[test]
+    fn one_minute_left_once() {
+        let mut tracker = AlertTracker::default();
+
+        assert!(tracker.check(RISK, Some((0, &task(61))), None).is_empty());
+        assert_eq!(
+            tracker.check(RISK, Some((0, &task(60))), None),
+            vec![Alert::OneMinuteLeft("a".to_string())]
+        );
+        assert!(tracker.check(RISK, Some((0, &task(59))), None).is_empty());
+    }
+
+    #[test]
+    fn time_up() {
+        let mut tracker = AlertTracker::default();
+        tracker.check(RISK, Some((0, &task(1))), None);
+
+        assert_eq!(
+            tracker.check(RISK, Some((0, &task(0))), None),
+            vec![Alert::TimeUp("a".to_string())]
+        );
+    }
+
+    #[test]
+    fn switching_tasks_is_not_crossing() {
+        let mut tracker = AlertTracker::default();
+        tracker.check(RISK, Some((1, &task(90))), None);
+
+        assert!(tracker.check(RISK, Some((0, &task(0))), None).is_empty());
+    }
+
+    #[test]
+    fn deadline_at_risk_until_recovered() {
+        let mut tracker = AlertTracker::default();
+
+        assert_eq!(
+            tracker.check(RISK, None, Some(0.4_f64)),
+            vec![Alert::DeadlineAtRisk]
+        );
+        assert!(tracker.check(RISK, None, Some(0.3_f64)).is_empty());
+        tracker.check(RISK, None, Some(0.8_f64));
+        assert_eq!(
+            tracker.check(RISK, None, Some(0.4_f64)),
+            vec![Alert::DeadlineAtRisk]
+        );
+    }
+}
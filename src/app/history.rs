@@ -0,0 +1,323 @@
+// Undo and redo for actions that change the routine.
+//
+// Time keeps passing between an action and its undo, so undoing can't just put back a
+// copy of the old routine: that would also roll back the time spent since. Instead, each
+// command knows how to reverse itself on the routine as it is now. The list pointer
+// doesn't track time, so it is restored from a copy.
+
+use std::time::Duration;
+
+use crate::app::list_pointer::ListPointer;
+use crate::routine::{CompletionStatus, Routine, Task};
+
+/// An action that changed the routine, with what's needed to reverse it.
+#[derive(Debug)]
+pub enum Command {
+    Status {
+        index: usize,
+        name: String,
+        before: CompletionStatus,
+        after: CompletionStatus,
+    },
+    Insert {
+        index: usize,
+        task: Task,
+    },
+    Delete {
+        index: usize,
+        task: Task,
+    },
+    Edit {
+        index: usize,
+        before: (String, Duration),
+        after: (String, Duration),
+    },
+    Move {
+        name: String,
+        from: usize,
+        to: usize,
+    },
+}
+
+impl Command {
+    fn undo(&mut self, tasks: &mut Routine) {
+        match self {
+            Self::Status { index, before, .. } => {
+                let _ = tasks.set_status(Some(*index), *before);
+            }
+            Self::Insert { index, task } => {
+                // keep the task as it is now, time spent included, to redo with
+                if let Some(removed) = tasks.remove(Some(*index)) {
+                    *task = removed;
+                }
+            }
+            Self::Delete { index, task } => tasks.restore(*index, task.clone()),
+            Self::Edit { index, before, .. } => {
+                let _ = tasks.edit(Some(*index), &before.0, before.1);
+            }
+            Self::Move { from, to, .. } => {
+                let _ = tasks.move_task(*to, *from);
+            }
+        }
+    }
+
+    fn redo(&mut self, tasks: &mut Routine) {
+        match self {
+            Self::Status { index, after, .. } => {
+                let _ = tasks.set_status(Some(*index), *after);
+            }
+            // undoing an insert removed the task, so put it back the same way
+            Self::Insert { index, task } => tasks.restore(*index, task.clone()),
+            Self::Delete { index, task } => {
+                if let Some(removed) = tasks.remove(Some(*index)) {
+                    *task = removed;
+                }
+            }
+            Self::Edit { index, after, .. } => {
+                let _ = tasks.edit(Some(*index), &after.0, after.1);
+            }
+            Self::Move { from, to, .. } => {
+                let _ = tasks.move_task(*from, *to);
+            }
+        }
+    }
+
+    /// Makes room for a task put in at `at` after the command was done, without a command of
+    /// its own. Returns where that task would have been before the command.
+    fn make_room(&mut self, at: usize) -> usize {
+        let shift = |index: &mut usize, at: usize| {
+            if *index >= at {
+                *index += 1;
+            }
+        };
+        match self {
+            Self::Status { index, .. } | Self::Edit { index, .. } => {
+                shift(index, at);
+                at
+            }
+            Self::Insert { index, .. } if *index >= at => {
+                *index += 1;
+                at
+            }
+            Self::Insert { .. } => at - 1,
+            Self::Delete { index, .. } if *index >= at => {
+                *index += 1;
+                at
+            }
+            Self::Delete { .. } => at + 1,
+            Self::Move { from, to, .. } => {
+                let before_moved = at <= *to;
+                shift(to, at);
+                // where it is among the tasks that didn't move
+                let at = if before_moved { at } else { at - 1 };
+                if *from >= at {
+                    *from += 1;
+                    at
+                } else {
+                    at + 1
+                }
+            }
+        }
+    }
+
+    /// The name of the task the command acted on, and what it did, for the log.
+    pub fn describe(&self) -> (&str, &'static str) {
+        match self {
+            Self::Status {
+                name,
+                before,
+                after,
+                ..
+            } => {
+                let action = match (before, after) {
+                    (_, CompletionStatus::Done) => "completed",
+                    (CompletionStatus::Done, _) => "uncompleted",
+                    (_, CompletionStatus::Skipped) => "skipped",
+                    (CompletionStatus::Skipped, _) => "unskipped",
+                    (CompletionStatus::NotYet, CompletionStatus::NotYet) => "unchanged",
+                };
+                (name, action)
+            }
+            Self::Insert { task, .. } => (&task.name, "added"),
+            Self::Delete { task, .. } => (&task.name, "deleted"),
+            Self::Edit { after, .. } => (&after.0, "edited"),
+            Self::Move { name, .. } => (name, "moved"),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Entry {
+    command: Command,
+    /// The pointer from before the command.
+    before: ListPointer,
+    /// The pointer from after the command.
+    after: ListPointer,
+}
+
+/// Undo and redo stacks.
+#[derive(Debug, Default)]
+pub struct History {
+    undo: Vec<Entry>,
+    redo: Vec<Entry>,
+}
+
+impl History {
+    /// Records a command that was just done. Anything that was undone can't be redone
+    /// anymore.
+    pub fn record(&mut self, command: Command, before: ListPointer, after: ListPointer) {
+        self.undo.push(Entry {
+            command,
+            before,
+            after,
+        });
+        self.redo.clear();
+    }
+
+    /// Keeps the commands pointing at the same tasks after one was put in at `at` without
+    /// being recorded, like a break. Anything that was undone can't be redone anymore.
+    pub fn make_room(&mut self, at: usize) {
+        let mut at = at;
+        for entry in self.undo.iter_mut().rev() {
+            entry.after.insert_item(at);
+            at = entry.command.make_room(at);
+            entry.before.insert_item(at);
+        }
+        self.redo.clear();
+    }
+
+    /// Reverses the most recent command, if any, and returns it.
+    pub fn undo(&mut self, tasks: &mut Routine, pointer: &mut ListPointer) -> Option<&Command> {
+        let mut entry = self.undo.pop()?;
+        entry.command.undo(tasks);
+        *pointer = entry.before;
+        self.redo.push(entry);
+        self.redo.last().map(|e| &e.command)
+    }
+
+    /// Does the most recently undone command again, if any, and returns it.
+    pub fn redo(&mut self, tasks: &mut Routine, pointer: &mut ListPointer) -> Option<&Command> {
+        let mut entry = self.redo.pop()?;
+        entry.command.redo(tasks);
+        *pointer = entry.after;
+        self.undo.push(entry);
+        self.undo.last().map(|e| &e.command)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn routine() -> Routine {
+        Routine::with_tasks(vec![Task::new("a", 60), Task::new("b", 60)])
+    }
+
+    #[test]
+    fn undo_status_keeps_time_spent() {
+        let mut tasks = routine();
+        let mut pointer = ListPointer::new(2);
+        let mut history = History::default();
+        tasks.toggle(Some(0)).unwrap();
+        history.record(
+            Command::Status {
+                index: 0,
+                name: "a".to_string(),
+                before: CompletionStatus::NotYet,
+                after: CompletionStatus::Done,
+            },
+            pointer,
+            pointer,
+        );
+        tasks.elapse(Some(1), Duration::new(10, 0));
+
+        history.undo(&mut tasks, &mut pointer).unwrap();
+
+        assert_eq!(tasks.tasks[0].status, CompletionStatus::NotYet);
+        assert_eq!(tasks.tasks[1].elapsed, Duration::new(10, 0));
+    }
+
+    #[test]
+    fn undo_then_redo_insert() {
+        let mut tasks = routine();
+        let mut pointer = ListPointer::new(2);
+        let mut history = History::default();
+        let before = pointer;
+        let task = Task::new("c", 30);
+        tasks.insert(1, task.clone());
+        pointer.append_item();
+        history.record(Command::Insert { index: 1, task }, before, pointer);
+
+        history.undo(&mut tasks, &mut pointer).unwrap();
+        assert_eq!(tasks.tasks.len(), 2);
+        assert_eq!(pointer.length(), 2);
+
+        history.redo(&mut tasks, &mut pointer).unwrap();
+        assert_eq!(tasks.tasks[1].name, "c");
+        assert_eq!(pointer.length(), 3);
+        assert_eq!(tasks.flex_goal, Duration::new(150, 0));
+    }
+
+    #[test]
+    fn new_command_clears_redo() {
+        let mut tasks = routine();
+        let mut pointer = ListPointer::new(2);
+        let mut history = History::default();
+        let command = || Command::Move {
+            name: "a".to_string(),
+            from: 0,
+            to: 1,
+        };
+        tasks.move_task(0, 1).unwrap();
+        history.record(command(), pointer, pointer);
+        history.undo(&mut tasks, &mut pointer).unwrap();
+
+        tasks.move_task(0, 1).unwrap();
+        history.record(command(), pointer, pointer);
+
+        assert!(history.redo(&mut tasks, &mut pointer).is_none());
+    }
+
+    #[test]
+    fn undo_around_unrecorded_task() {
+        let mut tasks = routine();
+        let mut pointer = ListPointer::new(2);
+        let mut history = History::default();
+        tasks.move_task(1, 0).unwrap();
+        history.record(
+            Command::Move {
+                name: "b".to_string(),
+                from: 1,
+                to: 0,
+            },
+            pointer,
+            pointer,
+        );
+        let before = pointer;
+        pointer.select(Some(1)).unwrap();
+        let task = tasks.remove(Some(1)).unwrap();
+        pointer.delete_current();
+        history.record(Command::Delete { index: 1, task }, before, pointer);
+        // b, then a break nobody asked to undo
+        tasks.insert(1, Task::new("break", 30));
+        pointer.insert_item(1);
+        history.make_room(1);
+
+        history.undo(&mut tasks, &mut pointer).unwrap();
+        let names: Vec<&str> = tasks.tasks.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, ["b", "break", "a"]);
+        history.undo(&mut tasks, &mut pointer).unwrap();
+        let names: Vec<&str> = tasks.tasks.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, ["break", "a", "b"]);
+        assert_eq!(pointer.length(), 3);
+        assert!(history.redo(&mut tasks, &mut pointer).is_some());
+    }
+
+    #[test]
+    fn nothing_to_undo() {
+        let mut tasks = routine();
+        let mut pointer = ListPointer::new(2);
+
+        assert!(History::default().undo(&mut tasks, &mut pointer).is_none());
+    }
+}
@@ -0,0 +1,137 @@
+// User commands run on routine events, to tie the routine into other scripts.
+
+use chrono::{DateTime, Local};
+use std::process::Command;
+use std::time::Duration;
+
+use super::alerts::spawn;
+use crate::config::Hooks;
+use crate::routine::Task;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HookEvent {
+    RoutineStart,
+    TaskStart,
+    TaskDone,
+    TaskSkipped,
+    Pause,
+    Resume,
+    Overtime,
+    RoutineEnd,
+}
+
+impl HookEvent {
+    /// The name used for the event in the config file.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::RoutineStart => "routine_start",
+            Self::TaskStart => "task_start",
+            Self::TaskDone => "task_done",
+            Self::TaskSkipped => "task_skipped",
+            Self::Pause => "pause",
+            Self::Resume => "resume",
+            Self::Overtime => "overtime",
+            Self::RoutineEnd => "routine_end",
+        }
+    }
+
+    fn command(self, hooks: &Hooks) -> Option<&str> {
+        match self {
+            Self::RoutineStart => &hooks.routine_start,
+            Self::TaskStart => &hooks.task_start,
+            Self::TaskDone => &hooks.task_done,
+            Self::TaskSkipped => &hooks.task_skipped,
+            Self::Pause => &hooks.pause,
+            Self::Resume => &hooks.resume,
+            Self::Overtime => &hooks.overtime,
+            Self::RoutineEnd => &hooks.routine_end,
+        }
+        .as_deref()
+    }
+}
+
+/// What a hook is told about the event.
+#[derive(Debug)]
+pub struct HookDetails<'a> {
+    pub routine: &'a str,
+    pub task: Option<&'a Task>,
+    /// Time left in the whole routine.
+    pub remaining: Duration,
+    pub projected_end: DateTime<Local>,
+    /// The message given when unpausing.
+    pub reason: Option<&'a str>,
+}
+
+impl HookDetails<'_> {
+    /// The environment variables a hook gets. Durations are in whole seconds.
+    fn env(&self, event: HookEvent) -> Vec<(&'static str, String)> {
+        let mut vars = vec![
+            ("ACCORDION_EVENT", event.name().to_string()),
+            ("ACCORDION_ROUTINE", self.routine.to_string()),
+            ("ACCORDION_REMAINING", self.remaining.as_secs().to_string()),
+            ("ACCORDION_PROJECTED_END", self.projected_end.to_rfc3339()),
+        ];
+        if let Some(task) = self.task {
+            vars.extend([
+                ("ACCORDION_TASK", task.name.clone()),
+                (
+                    "ACCORDION_TASK_DURATION",
+                    task.duration.as_secs().to_string(),
+                ),
+                ("ACCORDION_TASK_ELAPSED", task.elapsed.as_secs().to_string()),
+            ]);
+        }
+        if let Some(reason) = self.reason {
+            vars.push(("ACCORDION_REASON", reason.to_string()));
+        }
+        vars
+    }
+}
+
+/// Runs the hook for `event` with `sh`, if there is one, without waiting for it.
+pub fn run(hooks: &Hooks, event: HookEvent, details: &HookDetails) {
+    let Some(script) = event.command(hooks) else {
+        return;
+    };
+    cli_log::debug!("Running {} hook", event.name());
+    let mut command = Command::new("sh");
+    command.args(["-c", script]).envs(details.env(event));
+    spawn(command);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn env_has_task_details() {
+        let mut task = Task::new("start music", 120);
+        task.elapse(Duration::from_secs(30));
+        let details = HookDetails {
+            routine: "morning",
+            task: Some(&task),
+            remaining: Duration::from_secs(600),
+            projected_end: Local::now(),
+            reason: None,
+        };
+
+        let env = details.env(HookEvent::TaskStart);
+
+        assert!(env.contains(&("ACCORDION_EVENT", "task_start".to_string())));
+        assert!(env.contains(&("ACCORDION_TASK", "start music".to_string())));
+        assert!(env.contains(&("ACCORDION_TASK_ELAPSED", "30".to_string())));
+        assert!(env.contains(&("ACCORDION_REMAINING", "600".to_string())));
+        assert!(!env.iter().any(|(name, _)| *name == "ACCORDION_REASON"));
+    }
+
+    #[test]
+    fn only_configured_events_have_commands() {
+        let hooks = Hooks {
+            task_done: Some("echo done".to_string()),
+            ..Hooks::default()
+        };
+
+        assert_eq!(HookEvent::TaskDone.command(&hooks), Some("echo done"));
+        assert_eq!(HookEvent::TaskSkipped.command(&hooks), None);
+    }
+}
@@ -41,8 +41,17 @@ impl ListPointer {
         }
     }
 
+    /// Announces to the pointer that the item it points to has been removed. The pointer
+    /// moves to the item that took its place, or to the new last item if it was at the end.
     pub fn delete_current(&mut self) {
-        todo!()
+        let Some(i) = self.selected else {
+            return;
+        };
+        self.length -= 1;
+        self.selected = match self.length {
+            0 => None,
+            _ => Some(i.min(self.length - 1)),
+        };
     }
 
     pub fn select(&mut self, i: Option<usize>) -> Result<(), ScrollError> {
@@ -152,9 +161,21 @@ impl ListPointer {
         self.length += 1;
     }
 
+    /// Announces to the pointer that an item has been added at `index`, before or after the
+    /// pointer.
+    pub fn insert_item(&mut self, index: usize) {
+        match self.selected() {
+            Some(selected) if index <= selected => self.prepend_item(),
+            _ => self.append_item(),
+        }
+    }
+
     /// Announces to the pointer that an item has been added anywhere AFTER the pointer. Does
-    /// not impact which item the pointer points to.
+    /// not impact which item the pointer points to, unless the list was empty.
     pub fn append_item(&mut self) {
+        if self.length == 0 {
+            self.selected = Some(0);
+        }
         self.length += 1;
     }
 }
@@ -180,7 +201,96 @@ mod tests {
     use super::*;
 
     #[test]
-    fn hello() {
-        panic!("dont forget to test this module");
+    fn empty_list_has_no_selection() {
+        let mut pointer = ListPointer::new(0);
+
+        assert_eq!(pointer.selected(), None);
+        assert!(matches!(pointer.try_next(), Err(ScrollError::EmptyList)));
+    }
+
+    #[test]
+    fn next_stops_at_end() {
+        let mut pointer = ListPointer::new(2);
+
+        pointer.try_next().unwrap();
+
+        assert!(matches!(pointer.try_next(), Err(ScrollError::EndOfList)));
+        assert_eq!(pointer.selected(), Some(1));
+    }
+
+    #[test]
+    fn paused_hides_selection() {
+        let mut pointer = ListPointer::new(3);
+        pointer.try_next().unwrap();
+
+        pointer.pause();
+        assert_eq!(pointer.selected(), None);
+        pointer.unpause();
+
+        assert_eq!(pointer.selected(), Some(1));
+    }
+
+    #[test]
+    fn next_selectable_skips_unselectable() {
+        let mut pointer = ListPointer::new(4);
+
+        pointer
+            .try_next_selectable([true, false, false, true].into_iter())
+            .unwrap();
+
+        assert_eq!(pointer.selected(), Some(3));
+    }
+
+    #[test]
+    fn prev_selectable_skips_unselectable() {
+        let mut pointer = ListPointer::new(4);
+        pointer.select(Some(3)).unwrap();
+
+        pointer
+            .try_prev_selectable([true, false, false, true].into_iter())
+            .unwrap();
+
+        assert_eq!(pointer.selected(), Some(0));
+    }
+
+    #[test]
+    fn prepend_keeps_same_item() {
+        let mut pointer = ListPointer::new(2);
+        pointer.select(Some(1)).unwrap();
+
+        pointer.prepend_item();
+
+        assert_eq!(pointer.selected(), Some(2));
+        assert_eq!(pointer.length(), 3);
+    }
+
+    #[test]
+    fn delete_last_moves_back() {
+        let mut pointer = ListPointer::new(2);
+        pointer.select(Some(1)).unwrap();
+
+        pointer.delete_current();
+
+        assert_eq!(pointer.selected(), Some(0));
+        assert_eq!(pointer.length(), 1);
+    }
+
+    #[test]
+    fn append_to_empty_selects() {
+        let mut pointer = ListPointer::new(0);
+
+        pointer.append_item();
+
+        assert_eq!(pointer.selected(), Some(0));
+    }
+
+    #[test]
+    fn delete_only_item_empties() {
+        let mut pointer = ListPointer::new(1);
+
+        pointer.delete_current();
+
+        assert_eq!(pointer.selected(), None);
+        assert_eq!(pointer.length(), 0);
     }
 }
@@ -33,32 +33,89 @@
 use chrono::{DateTime, Local};
 use color_eyre::eyre::{OptionExt, Result};
 use directories::ProjectDirs;
-use std::fs::{create_dir_all, File};
-use std::io::{BufWriter, Write};
-use std::path::PathBuf;
+use std::fs::{self, create_dir_all, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
-use crate::app::{Routine, Task};
+use crate::app::{
+    pause::Pause,
+    suggest::TaskHistory,
+    summary::{self, Summary},
+    Routine, Task,
+};
+use crate::routine::task::{
+    duration::{parse_duration, DurationUnit},
+    ChecklistItem,
+};
 
-#[derive(Debug, Copy, Clone)]
+/// Stands in for the task name in events that didn't happen during a task.
+const NO_TASK: &str = "(no task)";
+/// Stands in for the task name in the end of routine summary.
+const SUMMARY: &str = "(summary)";
+/// Starts the message for a task added during a session.
+const ADDED: &str = "added at position ";
+/// Starts the summary line with the total time for the routine.
+const FINISHED_IN: &str = "finished in ";
+/// Ends a line about one of the routines in a session of several.
+const FROM: &str = " \tfrom ";
+
+#[derive(Debug, Clone)]
 enum LogEvent {
     Elapsed(Duration),
     Complete(bool),
     Skip(bool),
+    Edit {
+        old_name: String,
+        old_duration: Duration,
+        new_duration: Duration,
+    },
+    /// Positions are counted from zero.
+    Add(usize),
+    /// A break put in between tasks, at a position counted from zero.
+    Break(usize),
+    Delete,
+    Move {
+        from: usize,
+        to: usize,
+    },
+    /// What was undone, like "completed".
+    Undo(&'static str),
+    Redo(&'static str),
+    Pause {
+        start: DateTime<Local>,
+        end: DateTime<Local>,
+        duration: Duration,
+        reason: String,
+    },
+    /// How far over, if known.
+    PauseOverBudget(Option<Duration>),
+    /// A checklist item, and whether it was ticked or unticked.
+    Checklist(String, bool),
 }
 
 #[derive(Debug)]
 pub struct LogElement {
     time: DateTime<Local>,
     task_name: String,
+    /// Which routine of the session the task is from, if the event is about a task.
+    source: Option<usize>,
     event: LogEvent,
 }
 
 impl LogElement {
     fn new(task: &Task, event: LogEvent) -> LogElement {
+        LogElement {
+            source: Some(task.source),
+            ..Self::named(&task.name, event)
+        }
+    }
+
+    fn named(task_name: &str, event: LogEvent) -> LogElement {
         LogElement {
             time: Local::now(),
-            task_name: task.name.clone(), //TODO dont clone? unsure
+            task_name: task_name.to_owned(), //TODO dont clone? unsure
+            source: None,
             event,
         }
     }
@@ -67,9 +124,9 @@ impl LogElement {
         // only combine two elapse events,
         // and only if they are for the same
         // task
-        if self.task_name == next.task_name {
-            match (self.event, next.event) {
-                (LogEvent::Elapsed(a), LogEvent::Elapsed(b)) => (
+        if self.task_name == next.task_name && self.source == next.source {
+            match (&self.event, &next.event) {
+                (&LogEvent::Elapsed(a), &LogEvent::Elapsed(b)) => (
                     Self {
                         event: LogEvent::Elapsed(a + b),
                         ..self
@@ -83,20 +140,63 @@ impl LogElement {
         }
     }
 
-    pub fn write(&self, file: &mut BufWriter<File>) {
+    /// Writes the event as a line of the log, ending with the name of the task's `routine`
+    /// if there is one.
+    pub fn write(&self, file: &mut impl Write, routine: Option<&str>) -> io::Result<()> {
         let time = self.time.format("%T");
         let name = &self.task_name;
-        let message = match self.event {
+        let message = match &self.event {
             LogEvent::Elapsed(d) => {
-                format!("{} elapsed", crate::ui::format_duration(d))
+                format!("{} elapsed", crate::ui::format_duration(*d))
             }
             LogEvent::Complete(true) => "completed".to_string(),
             LogEvent::Complete(false) => "uncompleted".to_string(),
             LogEvent::Skip(true) => "skipped".to_string(),
             LogEvent::Skip(false) => "unskipped".to_string(),
+            LogEvent::Edit {
+                old_name,
+                old_duration,
+                new_duration,
+            } => format!(
+                "edited from \"{old_name}\" {} to {}",
+                crate::ui::format_duration(*old_duration),
+                crate::ui::format_duration(*new_duration)
+            ),
+            // positions are written counting from one, like the routine table
+            LogEvent::Add(i) => format!("{ADDED}{}", i + 1),
+            LogEvent::Break(i) => format!("break put in at position {}", i + 1),
+            LogEvent::Delete => "deleted".to_string(),
+            LogEvent::Move { from, to } => {
+                format!("moved from position {} to {}", from + 1, to + 1)
+            }
+            LogEvent::Undo(action) => format!("undid {action}"),
+            LogEvent::Redo(action) => format!("redid {action}"),
+            LogEvent::Pause {
+                start,
+                end,
+                duration,
+                reason,
+            } => format!(
+                "paused {} from {} to {}: {reason}",
+                crate::ui::format_duration(*duration),
+                start.format("%T"),
+                end.format("%T"),
+            ),
+            LogEvent::PauseOverBudget(Some(over)) => {
+                format!(
+                    "pause budget exceeded by {}",
+                    crate::ui::format_duration(*over)
+                )
+            }
+            LogEvent::PauseOverBudget(None) => "pause budget exceeded".to_string(),
+            LogEvent::Checklist(item, true) => format!("ticked \"{item}\""),
+            LogEvent::Checklist(item, false) => format!("unticked \"{item}\""),
         };
 
-        writeln!(file, "{time} \t{name} \t{message:}").unwrap();
+        match routine {
+            Some(routine) => writeln!(file, "{time} \t{name} \t{message:}{FROM}{routine}"),
+            None => writeln!(file, "{time} \t{name} \t{message:}"),
+        }
     }
 
     pub fn elapsed(task: &Task, elapsed: Duration) -> LogElement {
@@ -118,37 +218,125 @@ impl LogElement {
     pub fn unskipped(task: &Task) -> LogElement {
         Self::new(task, LogEvent::Skip(false))
     }
+
+    pub fn added(task: &Task, position: usize) -> LogElement {
+        Self::new(task, LogEvent::Add(position))
+    }
+
+    pub fn break_put_in(task: &Task, position: usize) -> LogElement {
+        Self::new(task, LogEvent::Break(position))
+    }
+
+    pub fn deleted(task: &Task) -> LogElement {
+        Self::new(task, LogEvent::Delete)
+    }
+
+    pub fn moved(task: &Task, from: usize, to: usize) -> LogElement {
+        Self::new(task, LogEvent::Move { from, to })
+    }
+
+    pub fn undone(task_name: &str, action: &'static str) -> LogElement {
+        Self::named(task_name, LogEvent::Undo(action))
+    }
+
+    pub fn redone(task_name: &str, action: &'static str) -> LogElement {
+        Self::named(task_name, LogEvent::Redo(action))
+    }
+
+    /// `pause` has to be finished.
+    pub fn paused(pause: &Pause) -> LogElement {
+        let end = pause.end.unwrap_or(pause.start);
+        Self::named(
+            pause.task.as_deref().unwrap_or(NO_TASK),
+            LogEvent::Pause {
+                start: pause.start,
+                end,
+                duration: pause.duration(end),
+                reason: pause.reason.clone(),
+            },
+        )
+    }
+
+    pub fn pause_over_budget(pause: &Pause, over: Option<Duration>) -> LogElement {
+        Self::named(
+            pause.task.as_deref().unwrap_or(NO_TASK),
+            LogEvent::PauseOverBudget(over),
+        )
+    }
+
+    /// `item` as it is after being ticked or unticked.
+    pub fn checklist(task: &Task, item: &ChecklistItem) -> LogElement {
+        Self::new(task, LogEvent::Checklist(item.text.clone(), item.done))
+    }
+
+    /// `task` is the task after the edit.
+    pub fn edited(task: &Task, old_name: &str, old_duration: Duration) -> LogElement {
+        Self::new(
+            task,
+            LogEvent::Edit {
+                old_name: old_name.to_owned(),
+                old_duration,
+                new_duration: task.original_duration,
+            },
+        )
+    }
 }
 
-#[derive(Debug)]
 pub struct RoutineLogger {
-    file: BufWriter<File>,
+    file: Box<dyn Write>,
     event_buffer: Vec<LogElement>,
+    /// Where the session log is, if it is a file.
+    path: Option<PathBuf>,
+    /// File name of the routine, which starts the name of every log of it.
+    routine_name: String,
+    /// Names of the routines run back to back in the session, if there are several.
+    sources: Vec<String>,
 }
 
 impl RoutineLogger {
+    /// # Errors
+    ///
+    /// Will return an error if the log directory can't be found or created, or if the log
+    /// file can't be created.
     pub fn new(
         _routine: &Routine,
         start_time: &DateTime<Local>,
         routine_path: &str,
-    ) -> RoutineLogger {
-        let path = get_log_location(routine_path, start_time).expect("failed to find or access the program data directory, or your routine task isnt valid utf8");
+    ) -> Result<RoutineLogger> {
+        let path = get_log_location(routine_path, start_time)?;
         // creating the file will fail if the directory doesn't exist yet
-        create_dir_all(path.parent().expect("should always work"))
-            .expect("failed to create data directory");
-        let file = File::create(path).expect("failed to create file");
-        let file = BufWriter::new(file);
+        if let Some(dir) = path.parent() {
+            create_dir_all(dir)?;
+        }
+        let file = File::create(&path)?;
+        Ok(RoutineLogger {
+            path: Some(path),
+            routine_name: routine_file_name(routine_path)?.to_owned(),
+            ..Self::with_writer(BufWriter::new(file))
+        })
+    }
 
+    /// Logs to any writer instead of a session file. Useful for tests.
+    pub fn with_writer(file: impl Write + 'static) -> RoutineLogger {
         RoutineLogger {
-            file,
+            file: Box::new(file),
             event_buffer: vec![],
+            path: None,
+            routine_name: String::new(),
+            sources: vec![],
         }
     }
 
-    pub fn log_comment(&mut self, message: &str, time: DateTime<Local>) {
-        let time = time.format("%T");
-        writeln!(self.file, "{time} \t{message:}").unwrap();
-        // TODO refactor so that this is also a kind of LogElement!!
+    /// Names the routines the session's tasks are from, so each task's line in the log says
+    /// which one it's from. With only one, lines don't say.
+    pub fn set_sources(&mut self, sources: Vec<String>) {
+        self.sources = sources;
+    }
+
+    /// The name of the routine file, without its directory. Empty when not logging to a
+    /// session file.
+    pub fn routine_name(&self) -> &str {
+        &self.routine_name
     }
 
     pub fn log(&mut self, event: LogElement) {
@@ -165,25 +353,173 @@ impl RoutineLogger {
         }
     }
 
+    /// Writes the end of routine summary. The total time is written in a way that
+    /// [`RoutineLogger::past_totals`] can find again.
+    pub fn log_summary(&mut self, summary: &Summary) {
+        if let Some(e) = self.event_buffer.pop() {
+            self.write(&e);
+        }
+        let time = summary.finished_at.format("%T");
+        let mut lines: Vec<String> = summary
+            .tasks
+            .iter()
+            .map(|task| {
+                format!(
+                    "{}: planned {}, actual {}, {}",
+                    task.name,
+                    crate::ui::format_duration(task.planned),
+                    crate::ui::format_duration(task.actual),
+                    summary::format_delta(task.planned, task.actual)
+                )
+            })
+            .collect();
+        lines.push(format!(
+            "{FINISHED_IN}{}",
+            crate::ui::format_duration(summary.elapsed)
+        ));
+        // a record of each routine's share, so its history counts it whatever it ran with
+        for (routine, total) in self.sources.iter().zip(&summary.per_routine) {
+            lines.push(format!(
+                "{FINISHED_IN}{}{FROM}{routine}",
+                crate::ui::format_duration(*total)
+            ));
+        }
+        lines.extend(summary.totals());
+        for line in lines {
+            if let Err(e) = writeln!(self.file, "{time} \t{SUMMARY} \t{line}") {
+                cli_log::warn!("Failed to write to routine log: {e}");
+            }
+        }
+    }
+
+    /// The total times, pauses included, of earlier sessions that made it to the end with the
+    /// same routine on its own, or the same routines chained in the same order.
+    pub fn past_totals(&self) -> Vec<Duration> {
+        // Log file names are the routine file name followed by a timestamp.
+        let prefix = format!("{}-", self.routine_name);
+        self.past_logs()
+            .iter()
+            .filter(|(file_name, _)| file_name.starts_with(&prefix))
+            .filter_map(|(_, log)| {
+                log.lines()
+                    .filter_map(|line| line.split_once(FINISHED_IN))
+                    .filter(|(_, total)| !total.contains(FROM))
+                    .find_map(|(_, total)| parse_duration(total, DurationUnit::Seconds).ok())
+                    .map(Duration::from_secs)
+            })
+            .collect()
+    }
+
+    /// For each routine in the session, its name and the time spent on its tasks in earlier
+    /// sessions that made it to the end, whether it ran on its own or chained with others.
+    pub fn past_routine_totals(&self) -> Vec<(String, Vec<Duration>)> {
+        let logs = self.past_logs();
+        self.sources
+            .iter()
+            .map(|routine| {
+                let totals = logs
+                    .iter()
+                    .flat_map(|(_, log)| log.lines())
+                    .filter_map(|line| line.split_once(FINISHED_IN))
+                    .filter_map(|(_, total)| total.split_once(FROM))
+                    .filter(|(_, other)| same_routine(routine, other))
+                    .filter_map(|(total, _)| parse_duration(total, DurationUnit::Seconds).ok())
+                    .map(Duration::from_secs)
+                    .collect();
+                (routine.clone(), totals)
+            })
+            .collect()
+    }
+
+    /// Tasks added during earlier sessions of any routine, and how long tasks took in the
+    /// sessions that made it to the end.
+    pub fn task_history(&self) -> TaskHistory {
+        let mut history = TaskHistory::default();
+        for (_, log) in self.past_logs() {
+            for line in log.lines() {
+                let mut fields = line.splitn(3, " \t");
+                let (Some(_), Some(name), Some(message)) =
+                    (fields.next(), fields.next(), fields.next())
+                else {
+                    continue;
+                };
+                if message.starts_with(ADDED) && name != NO_TASK {
+                    history.added(name);
+                } else if name == SUMMARY {
+                    if let Some((task, actual)) = summary_actual(message) {
+                        history.took(task, actual);
+                    }
+                }
+            }
+        }
+        history
+    }
+
+    /// The file names and text of the logs of earlier sessions of any routine.
+    fn past_logs(&self) -> Vec<(String, String)> {
+        let Some(path) = &self.path else {
+            return vec![];
+        };
+        let Some(dir) = path.parent() else {
+            return vec![];
+        };
+        let Ok(entries) = fs::read_dir(dir) else {
+            return vec![];
+        };
+        entries
+            .filter_map(std::result::Result::ok)
+            .map(|entry| entry.path())
+            .filter(|p| p != path)
+            .filter_map(|p| {
+                let name = p.file_name()?.to_string_lossy().into_owned();
+                Some((name, fs::read_to_string(p).ok()?))
+            })
+            .collect()
+    }
+
     fn write(&mut self, log: &LogElement) {
-        log.write(&mut self.file);
+        let routine = log
+            .source
+            .and_then(|source| self.sources.get(source))
+            .filter(|_| self.sources.len() > 1);
+        if let Err(e) = log.write(&mut self.file, routine.map(String::as_str)) {
+            cli_log::warn!("Failed to write to routine log: {e}");
+        }
     }
 
     pub fn finish(&mut self) {
         if let Some(e) = self.event_buffer.pop() {
             self.write(&e);
         }
+        if let Err(e) = self.file.flush() {
+            cli_log::warn!("Failed to write to routine log: {e}");
+        }
     }
 }
 
+/// Whether two routine names from [`RoutineLogger::set_sources`] are the same file name, since
+/// how many directories a name has depends on what it ran with.
+fn same_routine(a: &str, b: &str) -> bool {
+    Path::new(a).file_name() == Path::new(b).file_name()
+}
+
+/// The task name and time spent in a summary line written by
+/// [`RoutineLogger::log_summary`], if it is one for a task.
+fn summary_actual(line: &str) -> Option<(&str, Duration)> {
+    let (task, rest) = line.rsplit_once(": planned ")?;
+    let (_, rest) = rest.split_once(", actual ")?;
+    let (actual, _) = rest.split_once(", ")?;
+    let secs = parse_duration(actual, DurationUnit::Seconds).ok()?;
+    Some((task, Duration::from_secs(secs)))
+}
+
 // TODO add a command to print out the log location
 //
 pub fn get_log_location(routine_path: &str, time: &DateTime<Local>) -> Result<PathBuf> {
     // TODO make this configurable: data dir or routine dir
-    let routine_path: PathBuf = routine_path.into();
     let routine_name = format!(
         "{}-{}",
-        routine_path.file_name().unwrap().to_str().unwrap(),
+        routine_file_name(routine_path)?,
         time.format("%FT%T")
     );
     let routine_name: PathBuf = routine_name.into();
@@ -198,3 +534,160 @@ pub fn get_log_location(routine_path: &str, time: &DateTime<Local>) -> Result<Pa
         })
         .ok_or_eyre("Could not find a data path for routine logs. Is home directory accessible?")
 }
+
+fn routine_file_name(routine_path: &str) -> Result<&str> {
+    Path::new(routine_path)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_eyre("Routine path has no file name, or it isn't valid UTF-8.")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_past_totals() {
+        let dir = std::env::temp_dir().join(format!("accordion-logs-{}", std::process::id()));
+        create_dir_all(&dir).unwrap();
+        let log = |name: &str, text: &str| fs::write(dir.join(name), text).unwrap();
+        log(
+            "morning-2025-01-01T07:00:00",
+            "07:20:00 \t(summary) \tfinished in 20m 0s\n",
+        );
+        log(
+            "morning-2025-01-02T07:00:00",
+            "07:10:00 \tbrush teeth \tcompleted\n",
+        );
+        log(
+            "evening-2025-01-02T19:00:00",
+            "19:30:00 \t(summary) \tfinished in 30m 0s\n",
+        );
+        // the session took longer than the time on the morning's own tasks
+        log(
+            "evening+morning-2025-01-03T19:00:00",
+            "19:45:00 \t(summary) \tfinished in 45m 0s\n\
+             19:45:00 \t(summary) \tfinished in 29m 0s \tfrom evening\n\
+             19:45:00 \t(summary) \tfinished in 15m 0s \tfrom home/morning\n",
+        );
+        log(
+            "morning-2025-01-04T07:00:00",
+            "07:20:00 \t(summary) \tfinished in 22m 0s\n\
+             07:20:00 \t(summary) \tfinished in 18m 0s \tfrom morning\n",
+        );
+        let mut logger = RoutineLogger {
+            path: Some(dir.join("morning-2025-01-05T07:00:00")),
+            routine_name: "morning".to_string(),
+            ..RoutineLogger::with_writer(io::sink())
+        };
+        logger.set_sources(vec!["morning".to_string()]);
+
+        let mut totals = logger.past_totals();
+        let mut routine_totals = logger.past_routine_totals();
+        fs::remove_dir_all(&dir).unwrap();
+        totals.sort();
+        routine_totals[0].1.sort();
+
+        let minutes = |m: u64| Duration::from_secs(m * 60);
+        assert_eq!(totals, [minutes(20), minutes(22)]);
+        assert_eq!(
+            routine_totals,
+            [("morning".to_string(), vec![minutes(15), minutes(18)])]
+        );
+    }
+
+    #[test]
+    fn chained_summary_has_each_routine() {
+        let path = std::env::temp_dir().join(format!("accordion-chained-{}", std::process::id()));
+        let mut routine = Routine::with_tasks(vec![Task::new("a", 60), Task::new("b", 60)]);
+        routine.tasks[1].source = 1;
+        routine.elapse(Some(0), Duration::from_secs(90));
+        let start = Local::now();
+        let summary = Summary::new(&routine, start, start, Duration::ZERO, 1.0, vec![]);
+        let mut logger = RoutineLogger::with_writer(File::create(&path).unwrap());
+        logger.set_sources(vec![
+            "kids/shoes.csv".to_string(),
+            "me/shoes.csv".to_string(),
+        ]);
+        logger.log_summary(&summary);
+        logger.finish();
+
+        let text = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert!(text.contains("\tfinished in 1m 30s \tfrom kids/shoes.csv\n"));
+        assert!(text.contains("\tfinished in 0s \tfrom me/shoes.csv\n"));
+    }
+
+    #[test]
+    fn lone_routine_logs_its_share() {
+        let path = std::env::temp_dir().join(format!("accordion-lone-{}", std::process::id()));
+        let mut routine = Routine::with_tasks(vec![Task::new("a", 60)]);
+        routine.elapse(Some(0), Duration::from_secs(30));
+        let start = Local::now();
+        let summary = Summary::new(
+            &routine,
+            start,
+            start + Duration::from_secs(40),
+            Duration::from_secs(10),
+            1.0,
+            vec![],
+        );
+        let mut logger = RoutineLogger::with_writer(File::create(&path).unwrap());
+        logger.set_sources(vec!["shoes.csv".to_string()]);
+        logger.log_summary(&summary);
+        logger.finish();
+
+        let text = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert!(text.contains("\tfinished in 40s\n"));
+        assert!(text.contains("\tfinished in 30s \tfrom shoes.csv\n"));
+    }
+
+    #[test]
+    fn chained_routines_are_named() {
+        let mut buffer = vec![];
+        let mut task = Task::new("shoes", 60);
+        task.source = 1;
+        let element = LogElement::completed(&task);
+        element.write(&mut buffer, Some("bedtime.csv")).unwrap();
+        element.write(&mut buffer, None).unwrap();
+
+        let text = String::from_utf8(buffer).unwrap();
+        let lines: Vec<&str> = text.lines().map(|line| &line[8..]).collect();
+        assert_eq!(
+            lines,
+            [
+                " \tshoes \tcompleted \tfrom bedtime.csv",
+                " \tshoes \tcompleted"
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_task_history() {
+        let dir = std::env::temp_dir().join(format!("accordion-history-{}", std::process::id()));
+        create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("evening-2025-01-02T19:00:00"),
+            "19:01:00 \tfeed cat \tadded at position 3\n\
+             19:30:00 \t(summary) \tfeed cat: planned 5m 0s, actual 3m 0s, -2m 0s\n\
+             19:30:00 \t(summary) \tfinished in 30m 0s\n",
+        )
+        .unwrap();
+        let logger = RoutineLogger {
+            path: Some(dir.join("morning-2025-01-03T07:00:00")),
+            routine_name: "morning".to_string(),
+            ..RoutineLogger::with_writer(io::sink())
+        };
+
+        let history = logger.task_history();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(history.added_names(), ["feed cat"]);
+        assert_eq!(
+            history.typical("Feed Cat"),
+            Some(Duration::from_secs(3 * 60))
+        );
+    }
+}
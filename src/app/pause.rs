@@ -0,0 +1,202 @@
+// Pauses taken during a session. The routine timer keeps running while paused, so the
+// time is spent either way: this is to know where it went.
+
+use chrono::{DateTime, Local};
+use std::time::Duration;
+
+use crate::config::PauseBudget;
+
+/// A single pause, finished or not.
+#[derive(Debug, Clone)]
+pub struct Pause {
+    pub start: DateTime<Local>,
+    pub end: Option<DateTime<Local>>,
+    /// The message submitted when unpausing.
+    pub reason: String,
+    /// Name of the task that was selected when pausing, if any.
+    pub task: Option<String>,
+    /// Which routine of the session was running when pausing, for its pause budget.
+    pub source: usize,
+}
+
+impl Pause {
+    /// How long the pause lasted, or has lasted so far.
+    pub fn duration(&self, now: DateTime<Local>) -> Duration {
+        let end = self.end.unwrap_or(now);
+        (end - self.start).to_std().unwrap_or(Duration::ZERO)
+    }
+}
+
+/// Every pause in the session.
+#[derive(Debug, Default)]
+pub struct Pauses {
+    finished: Vec<Pause>,
+    current: Option<Pause>,
+    /// Whether the budget warning has already been given.
+    warned: bool,
+}
+
+impl Pauses {
+    pub fn start(&mut self, now: DateTime<Local>, task: Option<String>, source: usize) {
+        self.warned = false;
+        self.current = Some(Pause {
+            start: now,
+            end: None,
+            reason: String::new(),
+            task,
+            source,
+        });
+    }
+
+    /// Ends the current pause and returns it, or `None` if not paused.
+    pub fn end(&mut self, now: DateTime<Local>, reason: &str) -> Option<&Pause> {
+        let mut pause = self.current.take()?;
+        pause.end = Some(now);
+        reason.clone_into(&mut pause.reason);
+        self.finished.push(pause);
+        self.finished.last()
+    }
+
+    pub fn current(&self) -> Option<&Pause> {
+        self.current.as_ref()
+    }
+
+    pub fn finished(&self) -> &[Pause] {
+        &self.finished
+    }
+
+    /// Time spent paused over the whole session, including the current pause.
+    pub fn total(&self, now: DateTime<Local>) -> Duration {
+        self.finished
+            .iter()
+            .chain(&self.current)
+            .map(|pause| pause.duration(now))
+            .sum()
+    }
+
+    /// How far past the budget the pausing in the current pause's routine has gone, if it
+    /// has. `slack` is the time to spare before the deadline, for [`PauseBudget::Deadline`].
+    pub fn over_budget(
+        &self,
+        budget: Option<PauseBudget>,
+        slack: Option<Duration>,
+        now: DateTime<Local>,
+    ) -> Option<Duration> {
+        let source = self.current.as_ref()?.source;
+        match budget? {
+            PauseBudget::Fixed(budget) => {
+                let total: Duration = self
+                    .finished
+                    .iter()
+                    .chain(&self.current)
+                    .filter(|pause| pause.source == source)
+                    .map(|pause| pause.duration(now))
+                    .sum();
+                (total > budget).then(|| total - budget)
+            }
+            // Pausing already eats into the slack, so the budget runs out exactly when
+            // the slack does. How far over isn't known: the tasks absorb it by shrinking.
+            PauseBudget::Deadline => slack.filter(Duration::is_zero),
+        }
+    }
+
+    /// Returns true only the first time this is called while over budget during a pause,
+    /// so that the warning is logged once per pause.
+    pub fn should_warn(&mut self, over_budget: bool) -> bool {
+        let warn = over_budget && !self.warned;
+        self.warned |= over_budget;
+        warn
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn minutes(m: u64) -> Duration {
+        Duration::from_secs(m * 60)
+    }
+
+    #[test]
+    fn total_includes_current() {
+        let start = Local::now();
+        let mut pauses = Pauses::default();
+        pauses.start(start, None, 0);
+        pauses.end(start + minutes(2), "phone call");
+        pauses.start(start + minutes(5), Some("brush teeth".to_string()), 0);
+
+        assert_eq!(pauses.total(start + minutes(6)), minutes(3));
+    }
+
+    #[test]
+    fn end_without_pause() {
+        let mut pauses = Pauses::default();
+
+        assert!(pauses.end(Local::now(), "").is_none());
+    }
+
+    #[test]
+    fn fixed_budget_exceeded() {
+        let start = Local::now();
+        let mut pauses = Pauses::default();
+        pauses.start(start, None, 0);
+        let budget = Some(PauseBudget::Fixed(minutes(2)));
+
+        assert_eq!(pauses.over_budget(budget, None, start + minutes(1)), None);
+        assert_eq!(
+            pauses.over_budget(budget, None, start + minutes(3)),
+            Some(minutes(1))
+        );
+    }
+
+    #[test]
+    fn fixed_budget_is_per_routine() {
+        let start = Local::now();
+        let mut pauses = Pauses::default();
+        pauses.start(start, None, 0);
+        pauses.end(start + minutes(3), "");
+        pauses.start(start + minutes(10), None, 1);
+        let budget = Some(PauseBudget::Fixed(minutes(2)));
+
+        assert_eq!(pauses.over_budget(budget, None, start + minutes(11)), None);
+        assert_eq!(
+            pauses.over_budget(budget, None, start + minutes(13)),
+            Some(minutes(1))
+        );
+    }
+
+    #[test]
+    fn deadline_budget_without_deadline() {
+        let start = Local::now();
+        let mut pauses = Pauses::default();
+        pauses.start(start, None, 0);
+
+        let over = pauses.over_budget(Some(PauseBudget::Deadline), None, start + minutes(60));
+
+        assert_eq!(over, None);
+    }
+
+    #[test]
+    fn deadline_budget_out_of_slack() {
+        let start = Local::now();
+        let mut pauses = Pauses::default();
+        pauses.start(start, None, 0);
+
+        let over = pauses.over_budget(
+            Some(PauseBudget::Deadline),
+            Some(Duration::ZERO),
+            start + minutes(1),
+        );
+
+        assert_eq!(over, Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn warns_once() {
+        let mut pauses = Pauses::default();
+
+        assert!(!pauses.should_warn(false));
+        assert!(pauses.should_warn(true));
+        assert!(!pauses.should_warn(true));
+    }
+}
@@ -0,0 +1,167 @@
+// The state of the running routine, written to files so status bars and scripts can show
+// it without the interface.
+
+use chrono::{DateTime, Local, TimeDelta};
+use color_eyre::eyre::{eyre, OptionExt, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use crate::config::StatusExport;
+use crate::ui::format_duration;
+
+const JSON_FILE: &str = "status.json";
+const TEXT_FILE: &str = "status.txt";
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct Status {
+    pub routine: String,
+    pub task: Option<String>,
+    /// Seconds left for the selected task.
+    pub task_remaining: Option<u64>,
+    pub projected_end: String,
+    /// Seconds ahead of schedule, or behind if negative.
+    pub ahead: i64,
+    pub paused: bool,
+    /// How far through the routine it is, from 0 to 1, as the config measures it.
+    #[serde(default)]
+    pub progress: f64,
+    /// All of the above in one line.
+    pub text: String,
+}
+
+impl Status {
+    pub fn new(
+        routine: &str,
+        task: Option<(&str, std::time::Duration)>,
+        projected_end: DateTime<Local>,
+        ahead: TimeDelta,
+        paused: bool,
+        progress: f64,
+        clock_format: &str,
+    ) -> Self {
+        let mut text = match (paused, task) {
+            (true, _) => "Paused".to_string(),
+            (false, Some((name, remaining))) => {
+                format!("{name} {} left", format_duration(remaining))
+            }
+            (false, None) => routine.to_string(),
+        };
+        text.push_str(&format!(
+            ", {:.0}% done, ends {} ({})",
+            progress * 100.0_f64,
+            projected_end.format(clock_format).to_string().trim(),
+            format_offset(ahead)
+        ));
+        Self {
+            routine: routine.to_string(),
+            task: task.map(|(name, _)| name.to_string()),
+            task_remaining: task.map(|(_, remaining)| remaining.as_secs()),
+            projected_end: projected_end.to_rfc3339(),
+            ahead: ahead.num_seconds(),
+            paused,
+            progress,
+            text,
+        }
+    }
+
+    /// Writes the status files, replacing the old ones whole so that readers never see half a
+    /// file.
+    pub fn write(&self, dir: &Path) -> Result<()> {
+        fs::create_dir_all(dir)?;
+        replace(&dir.join(JSON_FILE), &serde_json::to_string(self)?)?;
+        replace(&dir.join(TEXT_FILE), &format!("{}\n", self.text))
+    }
+
+    /// Reads the status written by a running routine.
+    pub fn read(config: &StatusExport) -> Result<Self> {
+        let dir = config.dir().ok_or_eyre(
+            "Nowhere to find the status: set `dir` in the `[status]` section of the config file.",
+        )?;
+        let json = fs::read_to_string(dir.join(JSON_FILE)).map_err(|_| {
+            eyre!(
+                "No status in {}. Is a routine running, with `enabled = true` in the `[status]` section of the config file?",
+                dir.display()
+            )
+        })?;
+        Ok(serde_json::from_str(&json)?)
+    }
+}
+
+/// Removes the status files, so a routine that's over doesn't look like it's still running.
+pub fn clear(dir: &Path) {
+    for file in [JSON_FILE, TEXT_FILE] {
+        let _ = fs::remove_file(dir.join(file));
+    }
+}
+
+fn replace(path: &Path, contents: &str) -> Result<()> {
+    let temporary = path.with_extension("tmp");
+    fs::write(&temporary, contents)?;
+    fs::rename(temporary, path)?;
+    Ok(())
+}
+
+/// Like `2m 0s ahead` or `1m 30s behind`.
+pub fn format_offset(ahead: TimeDelta) -> String {
+    let amount = format_duration(ahead.abs().to_std().unwrap_or_default());
+    if ahead < TimeDelta::zero() {
+        format!("{amount} behind")
+    } else {
+        format!("{amount} ahead")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::Duration;
+
+    #[test]
+    fn one_line() {
+        let end = Local::now();
+        let status = Status::new(
+            "morning",
+            Some(("brush teeth", Duration::from_secs(200))),
+            end,
+            TimeDelta::seconds(-90),
+            false,
+            0.25_f64,
+            "%H:%M",
+        );
+
+        assert_eq!(
+            status.text,
+            format!(
+                "brush teeth 3m 20s left, 25% done, ends {} (1m 30s behind)",
+                end.format("%H:%M")
+            )
+        );
+        assert_eq!(status.ahead, -90);
+    }
+
+    #[test]
+    fn write_then_read() {
+        let dir = std::env::temp_dir().join(format!("accordion-status-{}", std::process::id()));
+        let status = Status::new(
+            "morning",
+            None,
+            Local::now(),
+            TimeDelta::zero(),
+            true,
+            0.0_f64,
+            "%H:%M",
+        );
+        status.write(&dir).unwrap();
+
+        let config = StatusExport {
+            enabled: true,
+            dir: Some(dir.clone()),
+        };
+        assert_eq!(Status::read(&config).unwrap(), status);
+        clear(&dir);
+        assert!(Status::read(&config).is_err());
+        fs::remove_dir(dir).unwrap();
+    }
+}
@@ -0,0 +1,172 @@
+// Suggestions for the name of a task being added, from the routine and from tasks added in
+// earlier sessions.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::routine::Routine;
+
+/// How many suggestions to show at most.
+pub const MAX_SUGGESTIONS: usize = 5;
+
+/// What earlier sessions say about tasks. Names are compared ignoring case.
+#[derive(Debug, Default)]
+pub struct TaskHistory {
+    /// Names of tasks added during sessions, as first written.
+    added: Vec<String>,
+    /// Time spent on each task, every time it was in a finished session.
+    actual: HashMap<String, Vec<Duration>>,
+}
+
+impl TaskHistory {
+    pub fn added(&mut self, name: &str) {
+        if !self.added.iter().any(|n| n.eq_ignore_ascii_case(name)) {
+            self.added.push(name.to_string());
+        }
+    }
+
+    pub fn took(&mut self, name: &str, actual: Duration) {
+        if !actual.is_zero() {
+            self.actual
+                .entry(name.to_lowercase())
+                .or_default()
+                .push(actual);
+        }
+    }
+
+    pub fn added_names(&self) -> &[String] {
+        &self.added
+    }
+
+    /// The median time spent on the task, if it was ever spent.
+    pub fn typical(&self, name: &str) -> Option<Duration> {
+        let mut times = self.actual.get(&name.to_lowercase())?.clone();
+        times.sort();
+        times.get(times.len() / 2).copied()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Suggestion {
+    pub name: String,
+    /// How long the task usually takes, or is planned to take when it hasn't been done.
+    pub typical: Option<Duration>,
+}
+
+/// Names from the routine and from `history` that fuzzily match `typed`, best first.
+pub fn suggest(typed: &str, routine: &Routine, history: &TaskHistory) -> Vec<Suggestion> {
+    let typed = typed.trim();
+    if typed.is_empty() {
+        return vec![];
+    }
+    let mut names: Vec<(&str, Option<Duration>)> = vec![];
+    let candidates = routine
+        .tasks
+        .iter()
+        .map(|task| (task.name.as_str(), Some(task.original_duration)))
+        .chain(
+            history
+                .added_names()
+                .iter()
+                .map(|name| (name.as_str(), None)),
+        );
+    for (name, planned) in candidates {
+        if !names.iter().any(|(n, _)| n.eq_ignore_ascii_case(name)) {
+            names.push((name, planned));
+        }
+    }
+    let mut scored: Vec<(u32, Suggestion)> = names
+        .into_iter()
+        // already typed out in full, so there's nothing to complete
+        .filter(|(name, _)| !name.eq_ignore_ascii_case(typed))
+        .filter_map(|(name, planned)| {
+            let score = fuzzy_score(typed, name)?;
+            let typical = history.typical(name).or(planned);
+            Some((
+                score,
+                Suggestion {
+                    name: name.to_string(),
+                    typical,
+                },
+            ))
+        })
+        .collect();
+    scored.sort_by(|(a, x), (b, y)| {
+        b.cmp(a)
+            .then(x.name.len().cmp(&y.name.len()))
+            .then(x.name.cmp(&y.name))
+    });
+    scored
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, suggestion)| suggestion)
+        .collect()
+}
+
+/// How well `name` matches `typed`, if every character typed is in it, in order. Matches
+/// that run on from the one before, or start a word, count for more.
+fn fuzzy_score(typed: &str, name: &str) -> Option<u32> {
+    let name: Vec<char> = name.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut next = 0;
+    let mut last: Option<usize> = None;
+    for c in typed.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = next + name.get(next..)?.iter().position(|&n| n == c)?;
+        score += 1;
+        if last.is_some_and(|last| last + 1 == found) {
+            score += 2;
+        }
+        if found == 0 || name.get(found - 1).is_some_and(|n| n.is_whitespace()) {
+            score += 3;
+        }
+        last = Some(found);
+        next = found + 1;
+    }
+    Some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::routine::Task;
+
+    fn names(suggestions: &[Suggestion]) -> Vec<&str> {
+        suggestions.iter().map(|s| s.name.as_str()).collect()
+    }
+
+    #[test]
+    fn matches_in_order() {
+        assert!(fuzzy_score("fc", "feed cat").is_some());
+        assert!(fuzzy_score("cf", "feed cat").is_none());
+        assert!(fuzzy_score("feed", "feed cat") > fuzzy_score("fdca", "feed cat"));
+    }
+
+    #[test]
+    fn routine_and_history() {
+        let routine = Routine::with_tasks(vec![
+            Task::new("feed dog", 120),
+            Task::new("brush teeth", 180),
+        ]);
+        let mut history = TaskHistory::default();
+        history.added("feed cat");
+        history.added("Feed Dog");
+        history.took("feed cat", Duration::from_secs(60));
+        history.took("feed cat", Duration::from_secs(240));
+        history.took("feed cat", Duration::from_secs(90));
+
+        let suggestions = suggest("fee", &routine, &history);
+
+        assert_eq!(names(&suggestions), ["feed cat", "feed dog"]);
+        assert_eq!(suggestions[0].typical, Some(Duration::from_secs(90)));
+        assert_eq!(suggestions[1].typical, Some(Duration::from_secs(120)));
+    }
+
+    #[test]
+    fn nothing_for_a_finished_name() {
+        let routine = Routine::with_tasks(vec![Task::new("shower", 600)]);
+
+        assert!(suggest("Shower", &routine, &TaskHistory::default()).is_empty());
+        assert!(suggest("  ", &routine, &TaskHistory::default()).is_empty());
+    }
+}
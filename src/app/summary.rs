@@ -0,0 +1,219 @@
+// What happened in a routine, shown and logged once every task is done or skipped.
+
+use chrono::{DateTime, Local};
+use std::time::Duration;
+
+use crate::routine::{CompletionStatus, Routine};
+use crate::ui::format_duration;
+
+#[derive(Debug, Clone)]
+pub struct TaskSummary {
+    pub name: String,
+    /// The original duration.
+    pub planned: Duration,
+    /// Time actually spent.
+    pub actual: Duration,
+    pub status: CompletionStatus,
+}
+
+#[derive(Debug, Clone)]
+pub struct Summary {
+    pub tasks: Vec<TaskSummary>,
+    pub finished_at: DateTime<Local>,
+    /// Time from starting the session to finishing the routine.
+    pub elapsed: Duration,
+    pub paused: Duration,
+    /// How far through the routine it got, from 0 to 1, as the config measures it.
+    pub progress: f64,
+    pub deadline: Option<DateTime<Local>>,
+    /// Total times of earlier sessions of the same routine, or the same chain of routines.
+    pub past_totals: Vec<Duration>,
+    /// Time spent on the tasks of each routine in the session, breaks left out.
+    pub per_routine: Vec<Duration>,
+    /// Each routine's name and the time spent on its tasks in earlier sessions, to compare
+    /// with [`Summary::per_routine`].
+    pub past_per_routine: Vec<(String, Vec<Duration>)>,
+    /// What happened when trying to save the routine, if it was tried.
+    pub saved: Option<String>,
+}
+
+impl Summary {
+    pub fn new(
+        routine: &Routine,
+        start_time: DateTime<Local>,
+        finished_at: DateTime<Local>,
+        paused: Duration,
+        progress: f64,
+        past_totals: Vec<Duration>,
+    ) -> Self {
+        let tasks = routine
+            .tasks
+            .iter()
+            .map(|task| TaskSummary {
+                name: task.name.clone(),
+                planned: task.original_duration,
+                actual: task.elapsed,
+                status: task.status,
+            })
+            .collect();
+        let mut per_routine = vec![];
+        for task in routine.tasks.iter().filter(|task| !task.is_break) {
+            if per_routine.len() <= task.source {
+                per_routine.resize(task.source + 1, Duration::ZERO);
+            }
+            if let Some(total) = per_routine.get_mut(task.source) {
+                *total += task.elapsed;
+            }
+        }
+        Self {
+            tasks,
+            finished_at,
+            elapsed: (finished_at - start_time)
+                .to_std()
+                .unwrap_or(Duration::ZERO),
+            paused,
+            progress,
+            deadline: routine.deadline(),
+            past_totals,
+            per_routine,
+            past_per_routine: vec![],
+            saved: None,
+        }
+    }
+
+    pub fn skipped(&self) -> usize {
+        self.tasks
+            .iter()
+            .filter(|task| task.status == CompletionStatus::Skipped)
+            .count()
+    }
+
+    /// Whether the routine was done by the deadline, if there was one.
+    pub fn deadline_met(&self) -> Option<bool> {
+        self.deadline.map(|deadline| self.finished_at <= deadline)
+    }
+
+    pub fn past_average(&self) -> Option<Duration> {
+        average(&self.past_totals)
+    }
+
+    /// One line each for the totals, other than the total time.
+    pub fn totals(&self) -> Vec<String> {
+        let mut lines = vec![
+            format!("{:.0}% done", self.progress * 100.0_f64),
+            format!("paused {}", format_duration(self.paused)),
+            match self.skipped() {
+                1 => "skipped 1 task".to_string(),
+                n => format!("skipped {n} tasks"),
+            },
+        ];
+        if let (Some(deadline), Some(met)) = (self.deadline, self.deadline_met()) {
+            let verdict = if met { "met" } else { "missed" };
+            lines.push(format!("deadline {} {verdict}", deadline.format("%T")));
+        }
+        if let Some(average) = self.past_average() {
+            lines.push(format!(
+                "{} compared to the average of {} past runs, {}",
+                format_delta(average, self.elapsed),
+                self.past_totals.len(),
+                format_duration(average)
+            ));
+        }
+        for ((routine, past), total) in self.past_per_routine.iter().zip(&self.per_routine) {
+            if let Some(average) = average(past) {
+                lines.push(format!(
+                    "tasks from {routine}: {} compared to the average of {} past runs, {}",
+                    format_delta(average, *total),
+                    past.len(),
+                    format_duration(average)
+                ));
+            }
+        }
+        lines
+    }
+}
+
+fn average(totals: &[Duration]) -> Option<Duration> {
+    let runs = u32::try_from(totals.len()).ok()?;
+    totals.iter().sum::<Duration>().checked_div(runs)
+}
+
+/// How far `actual` was from `planned`, like `+1m 20s` for going over.
+pub fn format_delta(planned: Duration, actual: Duration) -> String {
+    if actual >= planned {
+        format!("+{}", format_duration(actual - planned))
+    } else {
+        format!("-{}", format_duration(planned - actual))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::routine::Task;
+
+    fn summary(past_totals: Vec<Duration>) -> Summary {
+        let mut routine = Routine::with_tasks(vec![Task::new("a", 60), Task::new("b", 60)]);
+        routine.elapse(Some(0), Duration::from_secs(90));
+        routine.toggle(Some(0)).unwrap();
+        routine.skip(Some(1)).unwrap();
+        let start = Local::now();
+        Summary::new(
+            &routine,
+            start,
+            start + Duration::from_secs(100),
+            Duration::from_secs(10),
+            0.5,
+            past_totals,
+        )
+    }
+
+    #[test]
+    fn per_task_times() {
+        let summary = summary(vec![]);
+
+        assert_eq!(summary.tasks[0].actual, Duration::from_secs(90));
+        assert_eq!(
+            format_delta(summary.tasks[0].planned, summary.tasks[0].actual),
+            "+30s"
+        );
+        assert_eq!(summary.skipped(), 1);
+        assert_eq!(summary.elapsed, Duration::from_secs(100));
+    }
+
+    #[test]
+    fn delta_under() {
+        assert_eq!(
+            format_delta(Duration::from_secs(90), Duration::from_secs(30)),
+            "-1m 0s"
+        );
+    }
+
+    #[test]
+    fn no_deadline_no_verdict() {
+        let summary = summary(vec![]);
+
+        assert_eq!(summary.deadline_met(), None);
+    }
+
+    #[test]
+    fn compares_to_past_average() {
+        let summary = summary(vec![Duration::from_secs(80), Duration::from_secs(120)]);
+
+        assert_eq!(summary.past_average(), Some(Duration::from_secs(100)));
+        assert!(summary
+            .totals()
+            .contains(&"+0s compared to the average of 2 past runs, 1m 40s".to_string()));
+    }
+
+    #[test]
+    fn compares_each_routine_with_its_own_past() {
+        let mut summary = summary(vec![]);
+        summary.past_per_routine = vec![("shoes.csv".to_string(), vec![Duration::from_secs(60)])];
+
+        assert!(summary.totals().contains(
+            &"tasks from shoes.csv: +30s compared to the average of 1 past runs, 1m 0s".to_string()
+        ));
+    }
+}
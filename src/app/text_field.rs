@@ -0,0 +1,146 @@
+// A single line of text being typed, with a cursor. Kept apart from any terminal library so
+// the app can run without one.
+
+/// Text and a cursor, which is counted in characters rather than bytes.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TextField {
+    text: String,
+    cursor: usize,
+}
+
+impl TextField {
+    /// A field holding `text`, with the cursor at the end.
+    pub fn with_text(text: impl Into<String>) -> Self {
+        let text = text.into();
+        let cursor = text.chars().count();
+        Self { text, cursor }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    pub fn insert(&mut self, c: char) {
+        let at = self.byte_index(self.cursor);
+        self.text.insert(at, c);
+        self.cursor += 1;
+    }
+
+    pub fn insert_str(&mut self, s: &str) {
+        s.chars().for_each(|c| self.insert(c));
+    }
+
+    /// Deletes the character before the cursor.
+    pub fn backspace(&mut self) {
+        if self.cursor > 0 {
+            self.cursor -= 1;
+            self.delete();
+        }
+    }
+
+    /// Deletes the character under the cursor.
+    pub fn delete(&mut self) {
+        let at = self.byte_index(self.cursor);
+        if at < self.text.len() {
+            self.text.remove(at);
+        }
+    }
+
+    /// Deletes the word before the cursor, and any spaces after it.
+    pub fn delete_word(&mut self) {
+        let before: Vec<char> = self.text.chars().take(self.cursor).collect();
+        let spaces = before
+            .iter()
+            .rev()
+            .take_while(|c| c.is_whitespace())
+            .count();
+        let word = before
+            .iter()
+            .rev()
+            .skip(spaces)
+            .take_while(|c| !c.is_whitespace())
+            .count();
+        self.delete_before(spaces + word);
+    }
+
+    /// Deletes everything before the cursor.
+    pub fn delete_to_start(&mut self) {
+        self.delete_before(self.cursor);
+    }
+
+    fn delete_before(&mut self, count: usize) {
+        let start = self.byte_index(self.cursor - count);
+        let end = self.byte_index(self.cursor);
+        self.text.replace_range(start..end, "");
+        self.cursor -= count;
+    }
+
+    pub fn left(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    pub fn right(&mut self) {
+        self.cursor = (self.cursor + 1).min(self.text.chars().count());
+    }
+
+    pub fn home(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn end(&mut self) {
+        self.cursor = self.text.chars().count();
+    }
+
+    fn byte_index(&self, chars: usize) -> usize {
+        self.text
+            .char_indices()
+            .nth(chars)
+            .map_or(self.text.len(), |(i, _)| i)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edit_in_the_middle() {
+        let mut field = TextField::with_text("tea 5m");
+        field.home();
+        field.right();
+        field.insert_str("é");
+        field.backspace();
+        field.backspace();
+        field.insert('T');
+
+        assert_eq!(field.text(), "Tea 5m");
+        assert_eq!(field.cursor(), 1);
+    }
+
+    #[test]
+    fn delete_word() {
+        let mut field = TextField::with_text("brush teeth  ");
+
+        field.delete_word();
+        assert_eq!(field.text(), "brush ");
+        field.delete_to_start();
+        assert_eq!(field.text(), "");
+    }
+
+    #[test]
+    fn cursor_stays_in_text() {
+        let mut field = TextField::with_text("ab");
+        field.right();
+        field.delete();
+        assert_eq!(field.cursor(), 2);
+
+        field.home();
+        field.left();
+        field.backspace();
+        assert_eq!(field.text(), "ab");
+    }
+}
@@ -1,36 +1,172 @@
-use chrono::NaiveTime;
-use clap::Parser;
+use chrono::{Local, NaiveTime};
+use clap::{Parser, Subcommand, ValueEnum};
+use color_eyre::eyre::{bail, Result};
+use std::path::Path;
 
-use crate::config;
+use crate::app::{next_deadline, status::Status};
+use crate::config::{self, PauseBudget};
+use crate::control;
+use crate::routine::{self, plan::Plan, Routine};
 
 #[derive(Parser)]
 #[command(
     arg_required_else_help = true,
+    args_conflicts_with_subcommands = true,
     version = "alpha",
     about = "personal routine timing assistant"
 )]
 pub struct Cli {
-    /// Routine path
+    /// Routine paths, run back to back as one session. A file ending in `.playlist` lists
+    /// routine paths, one per line
     #[arg()]
-    pub routine_path: Option<String>,
+    pub routine_paths: Vec<String>,
     /// Deadline
     #[arg(short)]
     pub deadline: Option<NaiveTime>,
+    /// How much pausing is fine before warning, like 10m, or "deadline" to allow pausing
+    /// until tasks would have to shrink to make the deadline. Overrides the config file.
+    #[arg(long)]
+    pub pause_budget: Option<PauseBudget>,
+    /// Run without the terminal interface, taking commands (the same as for `ctl`) one per
+    /// line on standard input or through the control socket, and printing what happens
+    #[arg(long)]
+    pub headless: bool,
+    /// With --headless, print JSON lines instead of text
+    #[arg(long, requires = "headless")]
+    pub json: bool,
     /// Print config search path
     #[arg(long = "config")]
     pub config_path: bool,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Print the status of the running routine once. Needs status export turned on in the
+    /// config file.
+    Status {
+        /// Print JSON instead of a line of text
+        #[arg(long)]
+        json: bool,
+    },
+    /// Print when each task would start and end if the routine started now, without
+    /// starting it.
+    Plan {
+        /// Routine path
+        routine_path: String,
+        /// Deadline
+        #[arg(short)]
+        deadline: Option<NaiveTime>,
+        #[arg(long, value_enum, default_value_t)]
+        format: Format,
+    },
+    /// Check a routine file for problems, listing each with its line number. Exits with an
+    /// error if there are any. A playlist is checked along with the routines it lists.
+    Check {
+        /// Routine or playlist path
+        routine_path: String,
+    },
+    /// Send a command to the running routine: complete, skip, next, prev, pause,
+    /// resume [MESSAGE], add "NAME [DURATION]", status or quit. Needs the control socket turned on
+    /// in the config file.
+    #[cfg(unix)]
+    Ctl {
+        #[arg(required = true, trailing_var_arg = true)]
+        command: Vec<String>,
+    },
 }
 
 impl Cli {
-    pub fn run_instead_of_tui(&self) -> bool {
+    /// Does what was asked for on the command line if it isn't running a routine, and
+    /// returns whether it did.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if a subcommand fails.
+    pub fn run_instead_of_tui(&self) -> Result<bool> {
         if self.config_path {
             match config::find_config_location() {
                 Ok(p) => println!("{}", p.display()),
                 Err(_) => println!("Could not access home directory."),
             }
-            true
-        } else {
-            false
+            return Ok(true);
+        }
+        match &self.command {
+            None => Ok(false),
+            Some(Command::Status { json }) => {
+                let status = Status::read(&config::load().status)?;
+                if *json {
+                    println!("{}", serde_json::to_string(&status)?);
+                } else {
+                    println!("{}", status.text);
+                }
+                Ok(true)
+            }
+            Some(Command::Plan {
+                routine_path,
+                deadline,
+                format,
+            }) => {
+                print_plan(routine_path, *deadline, *format)?;
+                Ok(true)
+            }
+            Some(Command::Check { routine_path }) => {
+                let problems = routine::check::check_file(
+                    Path::new(routine_path),
+                    config::load().default_duration_unit,
+                )?;
+                if problems.is_empty() {
+                    println!("{routine_path}: ok");
+                    return Ok(true);
+                }
+                for (path, problem) in &problems {
+                    eprintln!("{}:{}: {}", path.display(), problem.line, problem.message);
+                }
+                match problems.len() {
+                    1 => bail!("{routine_path} has a problem"),
+                    count => bail!("{routine_path} has {count} problems"),
+                }
+            }
+            #[cfg(unix)]
+            Some(Command::Ctl { command }) => {
+                let path = config::load().control.socket();
+                let answer = control::send(&path, &command.join(" "))?;
+                if !answer.is_empty() {
+                    println!("{answer}");
+                }
+                Ok(true)
+            }
         }
     }
 }
+
+#[derive(Clone, Copy, Default, ValueEnum)]
+pub enum Format {
+    #[default]
+    Text,
+    Json,
+}
+
+fn print_plan(routine_path: &str, deadline: Option<NaiveTime>, format: Format) -> Result<()> {
+    let config = config::load();
+    let mut routine = Routine::with_tasks(routine::parse::read_csv(
+        routine_path,
+        config.default_duration_unit,
+    )?);
+    routine.set_buffer(config.transition_buffer.0);
+    let now = Local::now();
+    if let Some(deadline) = deadline {
+        routine.set_deadline_at(next_deadline(now, deadline)?, now);
+    }
+    let name = Path::new(routine_path)
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    let plan = Plan::new(&name, &routine, now, config.alerts.deadline_risk);
+    match format {
+        Format::Text => print!("{}", plan.to_text(config.clock_format.get_strftime())),
+        Format::Json => println!("{}", serde_json::to_string(&plan)?),
+    }
+    Ok(())
+}
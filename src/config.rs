@@ -1,8 +1,16 @@
 use color_eyre::eyre::{OptionExt, Result};
 use directories::ProjectDirs;
 use serde::Deserialize;
-use std::fs;
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+use std::{env, fs};
+
+use crate::routine::{
+    task::duration::{parse_duration, DurationUnit},
+    ProgressMetric,
+};
 
 /// Config file format. This can be specified by the user by creating a `.toml` file in a
 /// location that the [directories
@@ -13,6 +21,193 @@ use std::path::PathBuf;
 #[serde(default)]
 pub struct Config {
     pub clock_format: ClockFormat,
+    pub layout: LayoutPreference,
+    /// Show each task's duration from the routine file in the routine table, with how far
+    /// it has been shrunk or grown since. Off by default.
+    pub original_column: bool,
+    /// The unit for durations written as bare numbers, like `45`, in routine files and new
+    /// tasks: `hours`, `minutes` (the default) or `seconds`.
+    pub default_duration_unit: DurationUnit,
+    /// How much pausing is fine before Accordion Task warns about it. No warnings if unset.
+    /// Can be overridden for a single run with `--pause-budget`.
+    pub pause_budget: Option<PauseBudget>,
+    /// Pause budgets for particular routines, in the `[pause_budgets]` section, like
+    /// `"bedtime.csv" = "5m"`. A routine matches if its path ends with the name. Routines
+    /// that don't match any use `pause_budget`.
+    pub pause_budgets: HashMap<String, PauseBudget>,
+    /// Time set aside for switching between tasks, like `30s`, for tasks that don't set
+    /// their own. None by default.
+    pub transition_buffer: DurationSetting,
+    pub breaks: Breaks,
+    pub progress: Progress,
+    pub alerts: Alerts,
+    pub hooks: Hooks,
+    pub status: StatusExport,
+    pub control: Control,
+}
+
+/// A socket other programs can drive the running routine through, set in the `[control]`
+/// section.
+#[derive(Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct Control {
+    /// Listen for commands. Off by default.
+    pub enabled: bool,
+    /// Where the socket goes. Defaults to `accordion_task/control.sock` in
+    /// `$XDG_RUNTIME_DIR`, or in the temporary directory if that isn't set.
+    pub socket: Option<PathBuf>,
+}
+
+impl Control {
+    pub fn socket(&self) -> PathBuf {
+        self.socket.clone().unwrap_or_else(|| {
+            runtime_dir()
+                .unwrap_or_else(|| {
+                    // one for each user, since the temporary directory is shared
+                    let user = env::var("USER").unwrap_or_default();
+                    env::temp_dir().join(format!("accordion_task-{user}"))
+                })
+                .join("control.sock")
+        })
+    }
+}
+
+impl Config {
+    /// The pause budget for the routine at `path`: its own from `[pause_budgets]`, or else
+    /// `pause_budget`.
+    pub fn pause_budget_for(&self, path: &Path) -> Option<PauseBudget> {
+        self.pause_budgets
+            .iter()
+            .filter(|(routine, _)| path.ends_with(routine))
+            // like "kids/shoes.csv" over "shoes.csv"
+            .max_by_key(|(routine, _)| Path::new(routine).components().count())
+            .map(|(_, budget)| *budget)
+            .or(self.pause_budget)
+    }
+}
+
+/// Breaks put in after a stretch of time on tasks, set in the `[breaks]` section.
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+pub struct Breaks {
+    /// Put in a break after this much time on tasks, like `50m`. No breaks if unset.
+    pub every: Option<DurationSetting>,
+    /// How long each break is. Defaults to 10 minutes.
+    pub length: DurationSetting,
+    /// Keep breaks at their full length when tasks shrink to fit. Off by default.
+    pub rigid: bool,
+}
+
+impl Default for Breaks {
+    fn default() -> Self {
+        Self {
+            every: None,
+            length: DurationSetting(Duration::from_secs(10 * 60)),
+            rigid: false,
+        }
+    }
+}
+
+/// How progress through the routine is measured, for the progress bar, the summary and the
+/// status, set in the `[progress]` section.
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+pub struct Progress {
+    /// `time_weighted` (the default) for the share of planned time in finished tasks,
+    /// `task_count` for the share of tasks finished, or `elapsed_vs_goal` for time spent
+    /// against the time the routine has.
+    pub metric: ProgressMetric,
+    /// Whether skipped tasks count as finished. On by default.
+    pub count_skipped: bool,
+}
+
+impl Default for Progress {
+    fn default() -> Self {
+        Self {
+            metric: ProgressMetric::default(),
+            count_skipped: true,
+        }
+    }
+}
+
+/// Writing the status of the running routine to files for status bars, set in the
+/// `[status]` section.
+#[derive(Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct StatusExport {
+    /// Write `status.json` and `status.txt` every tick. Off by default.
+    pub enabled: bool,
+    /// Where to write the files. Defaults to `accordion_task` in `$XDG_RUNTIME_DIR`.
+    pub dir: Option<PathBuf>,
+}
+
+impl StatusExport {
+    /// The directory the status files go in, if there is one.
+    pub fn dir(&self) -> Option<PathBuf> {
+        self.dir.clone().or_else(runtime_dir)
+    }
+}
+
+/// `accordion_task` in `$XDG_RUNTIME_DIR`, for files that only matter while running.
+fn runtime_dir() -> Option<PathBuf> {
+    env::var_os("XDG_RUNTIME_DIR").map(|dir| PathBuf::from(dir).join("accordion_task"))
+}
+
+/// Shell commands to run when things happen in a routine, set in the `[hooks]` section.
+/// They run in the background with details about the event in `ACCORDION_*` environment
+/// variables.
+#[derive(Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct Hooks {
+    pub routine_start: Option<String>,
+    /// When a task that isn't done yet becomes the selected task.
+    pub task_start: Option<String>,
+    pub task_done: Option<String>,
+    pub task_skipped: Option<String>,
+    pub pause: Option<String>,
+    pub resume: Option<String>,
+    /// When the selected task runs out of time.
+    pub overtime: Option<String>,
+    /// When every task is done or skipped.
+    pub routine_end: Option<String>,
+}
+
+/// Alerts for staying on schedule while away from the screen. Set in the `[alerts]`
+/// section.
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+pub struct Alerts {
+    /// Alert when the selected task's time runs out.
+    pub time_up: bool,
+    /// Alert when the selected task has one minute left.
+    pub one_minute_left: bool,
+    /// Alert when making the deadline would take shrinking the unfinished tasks to less than
+    /// `deadline_risk` of their original durations.
+    pub deadline_at_risk: bool,
+    /// From 0 to 1. Defaults to 0.5, so alerts come when tasks have to be cut in half.
+    pub deadline_risk: f64,
+    /// Ring the terminal bell.
+    pub bell: bool,
+    /// Program that shows desktop notifications, given a title and a message as arguments.
+    /// Defaults to `notify-send`. If the program can't be found, or this is set to an empty
+    /// string, there are no desktop notifications.
+    pub desktop_command: String,
+    /// Shell command that plays a sound, like `paplay ~/ding.ogg`. No sound by default.
+    pub sound_command: Option<String>,
+}
+
+impl Default for Alerts {
+    fn default() -> Self {
+        Self {
+            time_up: true,
+            one_minute_left: true,
+            deadline_at_risk: true,
+            deadline_risk: 0.5,
+            bell: true,
+            desktop_command: "notify-send".to_string(),
+            sound_command: None,
+        }
+    }
 }
 
 /// Time display format
@@ -40,6 +235,74 @@ pub enum ClockFormat {
     Custom(String),
 }
 
+/// Which layout to draw the routine with.
+///
+/// By default, the largest layout that fits in the terminal is picked, and the choice is
+/// revisited whenever the terminal is resized. Setting one of the other options always uses
+/// that layout, even if it gets cut off.
+#[derive(Deserialize, Default, Debug, Copy, Clone, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum LayoutPreference {
+    /// Pick a layout based on the terminal size.
+    #[default]
+    Auto,
+    /// Timer, active task, and routine table, each in their own panel.
+    Full,
+    /// A one line timer above the routine table.
+    Compact,
+    /// Only the current task, its remaining time, and the projected end time.
+    Minimal,
+}
+
+/// How much time can be spent paused.
+///
+/// Written as a duration like `10m`, or as `deadline`.
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq)]
+#[serde(try_from = "String")]
+pub enum PauseBudget {
+    /// A fixed amount of pausing over the whole routine.
+    Fixed(Duration),
+    /// Pausing is fine until the remaining tasks would have to shrink to make the deadline.
+    /// Without a deadline, there is no limit.
+    Deadline,
+}
+
+impl FromStr for PauseBudget {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        if s.trim() == "deadline" {
+            return Ok(Self::Deadline);
+        }
+        parse_duration(s, DurationUnit::Minutes)
+            .map(|secs| Self::Fixed(Duration::from_secs(secs)))
+            .map_err(|_| format!("{s:?} is not a duration like 10m or the word deadline"))
+    }
+}
+
+impl TryFrom<String> for PauseBudget {
+    type Error = String;
+
+    fn try_from(s: String) -> std::result::Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+/// A length of time, written as a duration like `30s`.
+#[derive(Deserialize, Debug, Default, Copy, Clone, PartialEq)]
+#[serde(try_from = "String")]
+pub struct DurationSetting(pub Duration);
+
+impl TryFrom<String> for DurationSetting {
+    type Error = String;
+
+    fn try_from(s: String) -> std::result::Result<Self, Self::Error> {
+        parse_duration(&s, DurationUnit::Minutes)
+            .map(|secs| Self(Duration::from_secs(secs)))
+            .map_err(|e| format!("can't read duration {s:?}: {e}"))
+    }
+}
+
 impl ClockFormat {
     pub fn get_strftime(&self) -> &str {
         match self {
@@ -56,11 +319,7 @@ impl ClockFormat {
 /// Does not check that the later parts of the path exist.
 pub fn find_config_location() -> Result<PathBuf> {
     ProjectDirs::from("", "", "Accordion Task")
-        .map(|dirs| {
-            dirs.config_local_dir()
-                .to_owned()
-                .join("config.toml")
-        })
+        .map(|dirs| dirs.config_local_dir().to_owned().join("config.toml"))
         .ok_or_eyre("Could not find a config path.")
 }
 
@@ -75,7 +334,7 @@ fn try_load() -> Result<Config> {
 pub fn load() -> Config {
     match try_load() {
         Err(e) => {
-            println!("Warning: Config file failed with error {e} Falling back to default config.");
+            eprintln!("Warning: Config file failed with error {e} Falling back to default config.");
             Config::default()
         }
         Ok(c) => c,
@@ -0,0 +1,98 @@
+#[cfg(unix)]
+mod socket;
+
+use std::{str::FromStr, sync::mpsc, time::Duration};
+
+use crate::event::Event;
+
+#[cfg(unix)]
+pub use socket::{send, ControlSocket};
+
+/// How long a client waits for the routine to answer.
+const REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Commands other programs can send to a running routine, one per line.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ControlCommand {
+    Complete,
+    Skip,
+    Next,
+    Prev,
+    Pause,
+    /// Unpause, with a message for the log.
+    Resume(String),
+    /// Append a task, written the same way as when adding one by hand.
+    Add(String),
+    Status,
+    Quit,
+}
+
+impl FromStr for ControlCommand {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (command, rest) = s.split_once(' ').unwrap_or((s, ""));
+        // `add "brush teeth 5m"` and `add brush teeth 5m` are the same
+        let rest = rest.trim().trim_matches('"');
+        match command {
+            "complete" => Ok(Self::Complete),
+            "skip" => Ok(Self::Skip),
+            "next" => Ok(Self::Next),
+            "prev" => Ok(Self::Prev),
+            "pause" => Ok(Self::Pause),
+            "resume" => Ok(Self::Resume(rest.to_string())),
+            "add" if rest.is_empty() => Err("add needs a task, like add \"stretch 5m\"".to_string()),
+            "add" => Ok(Self::Add(rest.to_string())),
+            "status" => Ok(Self::Status),
+            "quit" => Ok(Self::Quit),
+            _ => Err(format!(
+                "unknown command {command:?}; try complete, skip, next, prev, pause, resume, add, status or quit"
+            )),
+        }
+    }
+}
+
+/// A command from the socket, with where to send the answer.
+#[derive(Debug, Clone)]
+pub struct ControlRequest {
+    pub command: ControlCommand,
+    pub reply: mpsc::Sender<Result<String, String>>,
+}
+
+/// Sends the command on `line` to the routine through `sender`, and waits for the answer.
+pub fn answer(line: &str, sender: &mpsc::Sender<Event>) -> Result<String, String> {
+    let command = line.parse()?;
+    let (reply, answer) = mpsc::channel();
+    sender
+        .send(Event::Control(ControlRequest { command, reply }))
+        .map_err(|_| "the routine has quit".to_string())?;
+    answer
+        .recv_timeout(REPLY_TIMEOUT)
+        .map_err(|_| "the routine didn't answer".to_string())?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_add() {
+        assert_eq!(
+            "add \"stretch 5m\"".parse(),
+            Ok(ControlCommand::Add("stretch 5m".to_string()))
+        );
+        assert_eq!(
+            "add stretch 5m".parse(),
+            Ok(ControlCommand::Add("stretch 5m".to_string()))
+        );
+        assert!("add".parse::<ControlCommand>().is_err());
+    }
+
+    #[test]
+    fn parse_simple() {
+        assert_eq!(" complete\n".parse(), Ok(ControlCommand::Complete));
+        assert_eq!("resume".parse(), Ok(ControlCommand::Resume(String::new())));
+        assert!("finish".parse::<ControlCommand>().is_err());
+    }
+}
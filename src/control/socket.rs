@@ -0,0 +1,171 @@
+// The Unix socket other programs drive a running routine through. Only built on Unix.
+
+use std::{
+    fs,
+    io::{self, BufRead, BufReader, Write},
+    os::unix::{
+        fs::FileTypeExt,
+        net::{UnixListener, UnixStream},
+    },
+    path::{Path, PathBuf},
+    sync::mpsc,
+    thread,
+};
+
+use color_eyre::eyre::{bail, eyre, Result};
+
+use super::answer;
+use crate::config::Control;
+use crate::event::Event;
+
+/// The control socket of a running routine. The socket file is removed when this is
+/// dropped.
+#[derive(Debug)]
+pub struct ControlSocket {
+    path: PathBuf,
+}
+
+impl ControlSocket {
+    /// Starts listening if the config says to.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the socket can't be started.
+    pub fn from_config(config: &Control, sender: mpsc::Sender<Event>) -> Result<Option<Self>> {
+        if !config.enabled {
+            return Ok(None);
+        }
+        let path = config.socket();
+        let socket = Self::listen(&path, sender)?;
+        cli_log::info!("Listening for commands on {}", path.display());
+        Ok(Some(socket))
+    }
+
+    /// Starts listening at `path` on another thread, sending each command to `sender` as an
+    /// [`Event::Control`].
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if another routine is already listening at `path`, if something
+    /// other than a socket is there, or if the socket can't be created.
+    pub fn listen(path: &Path, sender: mpsc::Sender<Event>) -> Result<Self> {
+        if path.exists() {
+            if UnixStream::connect(path).is_ok() {
+                bail!("Another routine is already listening on {}", path.display());
+            }
+            if !fs::metadata(path)?.file_type().is_socket() {
+                bail!(
+                    "{} isn't a socket, so it was left alone. Set `socket` in the `[control]` section of the config file to somewhere else.",
+                    path.display()
+                );
+            }
+            // left behind by a routine that didn't get to clean up
+            fs::remove_file(path)?;
+        }
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let listener = UnixListener::bind(path)?;
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else {
+                    continue;
+                };
+                let sender = sender.clone();
+                thread::spawn(move || {
+                    if let Err(e) = serve(stream, &sender) {
+                        cli_log::warn!("Control connection failed: {e}");
+                    }
+                });
+            }
+        });
+        Ok(Self {
+            path: path.to_path_buf(),
+        })
+    }
+}
+
+impl Drop for ControlSocket {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Answers every line on the connection until the client hangs up. Answers start with `ok`
+/// or `error`.
+fn serve(stream: UnixStream, sender: &mpsc::Sender<Event>) -> io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match answer(&line, sender) {
+            Ok(message) => writeln!(writer, "ok {message}")?,
+            Err(message) => writeln!(writer, "error {message}")?,
+        }
+    }
+    Ok(())
+}
+
+/// Sends `command` to the routine listening at `path` and returns its answer.
+///
+/// # Errors
+///
+/// Will return an error if nothing is listening at `path`, or if the routine answers with an
+/// error.
+pub fn send(path: &Path, command: &str) -> Result<String> {
+    let mut stream = UnixStream::connect(path).map_err(|e| {
+        eyre!(
+            "Couldn't connect to {}: {e}. Is a routine running, with `enabled = true` in the `[control]` section of the config file?",
+            path.display()
+        )
+    })?;
+    writeln!(stream, "{command}")?;
+    let mut answer = String::new();
+    BufReader::new(stream).read_line(&mut answer)?;
+    let answer = answer.trim_end();
+    match answer.split_once(' ').unwrap_or((answer, "")) {
+        ("ok", message) => Ok(message.to_string()),
+        ("error", message) => Err(eyre!("{message}")),
+        _ => Err(eyre!("Unexpected answer: {answer}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::ControlCommand;
+
+    #[test]
+    fn round_trip_through_socket() {
+        let path = std::env::temp_dir().join(format!("accordion-{}.sock", std::process::id()));
+        let (sender, receiver) = mpsc::channel();
+        let socket = ControlSocket::listen(&path, sender).unwrap();
+        thread::spawn(move || {
+            while let Ok(Event::Control(request)) = receiver.recv() {
+                let answer = match request.command {
+                    ControlCommand::Status => Ok("fine".to_string()),
+                    _ => Err("nope".to_string()),
+                };
+                request.reply.send(answer).unwrap();
+            }
+        });
+
+        assert_eq!(send(&path, "status").unwrap(), "fine");
+        assert!(send(&path, "skip").is_err());
+        drop(socket);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn leaves_other_files_alone() {
+        let path = std::env::temp_dir().join(format!("accordion-{}.notasock", std::process::id()));
+        fs::write(&path, "important").unwrap();
+        let (sender, _receiver) = mpsc::channel();
+
+        assert!(ControlSocket::listen(&path, sender).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "important");
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -7,8 +7,10 @@ use std::{
 use color_eyre::Result;
 use crossterm::event::{self, Event as CrosstermEvent, KeyEvent, MouseEvent};
 
+use crate::control::ControlRequest;
+
 /// Terminal events.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 pub enum Event {
     /// Terminal tick.
     Tick,
@@ -18,6 +20,8 @@ pub enum Event {
     Mouse(MouseEvent),
     /// Terminal resize.
     Resize(u16, u16),
+    /// Command from the control socket.
+    Control(ControlRequest),
 }
 
 /// Terminal event handler.
@@ -27,7 +31,6 @@ pub enum Event {
 #[derive(Debug)]
 pub struct EventHandler {
     /// Event sender channel.
-    #[allow(dead_code)]
     sender: mpsc::Sender<Event>,
     /// Event receiver channel.
     receiver: mpsc::Receiver<Event>,
@@ -50,8 +53,16 @@ impl EventHandler {
                         .checked_sub(last_tick.elapsed())
                         .unwrap_or(tick_rate);
 
-                    if event::poll(timeout).expect("unable to poll for event") {
-                        match event::read().expect("unable to read event") {
+                    // If the terminal stops answering or the receiver hangs up, there
+                    // is nobody left to send events to, so the thread stops.
+                    let Ok(ready) = event::poll(timeout) else {
+                        break;
+                    };
+                    if ready {
+                        let Ok(event) = event::read() else {
+                            break;
+                        };
+                        let sent = match event {
                             CrosstermEvent::Key(e) => {
                                 if e.kind == event::KeyEventKind::Press {
                                     sender.send(Event::Key(e))
@@ -61,13 +72,18 @@ impl EventHandler {
                             }
                             CrosstermEvent::Mouse(e) => sender.send(Event::Mouse(e)),
                             CrosstermEvent::Resize(w, h) => sender.send(Event::Resize(w, h)),
-                            _ => unimplemented!(),
+                            // focus changes and pastes aren't used
+                            _ => Ok(()),
+                        };
+                        if sent.is_err() {
+                            break;
                         }
-                        .expect("failed to send terminal event");
                     }
 
                     if last_tick.elapsed() >= tick_rate {
-                        sender.send(Event::Tick).expect("failed to send tick event");
+                        if sender.send(Event::Tick).is_err() {
+                            break;
+                        }
                         last_tick = Instant::now();
                     }
                 }
@@ -80,6 +96,31 @@ impl EventHandler {
         }
     }
 
+    /// Constructs an [`EventHandler`] that only ticks, for running without a terminal.
+    /// Other events come through [`EventHandler::sender`].
+    pub fn without_terminal(tick_rate: u64) -> Self {
+        let tick_rate = Duration::from_millis(tick_rate);
+        let (sender, receiver) = mpsc::channel();
+        let handler = {
+            let sender = sender.clone();
+            thread::spawn(move || {
+                while sender.send(Event::Tick).is_ok() {
+                    thread::sleep(tick_rate);
+                }
+            })
+        };
+        Self {
+            sender,
+            receiver,
+            handler,
+        }
+    }
+
+    /// Another way to send events, for sources other than the terminal.
+    pub fn sender(&self) -> mpsc::Sender<Event> {
+        self.sender.clone()
+    }
+
     /// Receive the next event from the handler thread.
     ///
     /// This function will always block the current thread if
@@ -0,0 +1,168 @@
+use std::{io, sync::mpsc, thread};
+
+use color_eyre::Result;
+use serde_json::json;
+
+use crate::app::{summary::Summary, App, Mode};
+#[cfg(unix)]
+use crate::control::ControlSocket;
+use crate::control::{self, ControlCommand, ControlRequest};
+use crate::event::{Event, EventHandler};
+use crate::ui::format_duration;
+use crate::update::control;
+
+/// Runs the routine without a terminal interface until it's finished or told to quit.
+/// Commands come one per line on standard input, or through the control socket. What
+/// happens is printed to standard output, as text or as JSON lines.
+///
+/// # Errors
+///
+/// Will return an error if the control socket or the signal handler can't be started.
+pub fn run(mut app: App, json: bool) -> Result<()> {
+    let events = EventHandler::without_terminal(500);
+    // Ctrl-C and kill quit like the quit command does, so the log gets written out
+    let signalled = events.sender();
+    ctrlc::set_handler(move || request_quit(&signalled))?;
+    // kept until the end, when dropping it removes the socket
+    #[cfg(unix)]
+    let _control = ControlSocket::from_config(&app.config.control, events.sender())?;
+    let sender = events.sender();
+    thread::spawn(move || {
+        // at the end of input, keep going: there may still be the socket
+        for line in io::stdin().lines().map_while(std::result::Result::ok) {
+            if !line.trim().is_empty() {
+                println!("{}", answer_line(&control::answer(&line, &sender), json));
+            }
+        }
+    });
+
+    let mut current = current_task(&app);
+    println!("{}", status_line(&app, json));
+    while !app.should_quit {
+        handle(&mut app, events.next()?);
+        if let (Mode::Summary, Some(summary)) = (&app.menu_focus, &app.summary) {
+            println!("{}", summary_line(summary, json));
+            app.quit();
+        } else if current_task(&app) != current {
+            current = current_task(&app);
+            println!("{}", status_line(&app, json));
+        }
+    }
+    Ok(())
+}
+
+fn handle(app: &mut App, event: Event) {
+    match event {
+        Event::Tick => app.tick(),
+        Event::Control(request) => {
+            // the client may have given up waiting, which is fine
+            let _ = request.reply.send(control(app, request.command));
+        }
+        Event::Key(_) | Event::Mouse(_) | Event::Resize(..) => {}
+    }
+}
+
+/// Asks the routine to quit, as if the quit command had come in. Nobody waits for the answer.
+fn request_quit(sender: &mpsc::Sender<Event>) {
+    let (reply, _) = mpsc::channel();
+    let _ = sender.send(Event::Control(ControlRequest {
+        command: ControlCommand::Quit,
+        reply,
+    }));
+}
+
+/// Index and name of the selected task, to notice moving on to another one.
+fn current_task(app: &App) -> (Option<usize>, Option<String>) {
+    (
+        app.task_widget_state.selected(),
+        app.get_current_task_name().map(str::to_string),
+    )
+}
+
+fn answer_line(answer: &Result<String, String>, json: bool) -> String {
+    match (answer, json) {
+        (Ok(message), false) => format!("ok {message}").trim_end().to_string(),
+        (Err(message), false) => format!("error {message}"),
+        (Ok(message), true) => {
+            json!({"type": "answer", "ok": true, "message": message}).to_string()
+        }
+        (Err(message), true) => {
+            json!({"type": "answer", "ok": false, "message": message}).to_string()
+        }
+    }
+}
+
+fn status_line(app: &App, json: bool) -> String {
+    let status = app.status();
+    if json {
+        json!({"type": "status", "status": status}).to_string()
+    } else {
+        status.text
+    }
+}
+
+fn summary_line(summary: &Summary, json: bool) -> String {
+    let totals = summary.totals();
+    if json {
+        json!({
+            "type": "finished",
+            "elapsed": summary.elapsed.as_secs(),
+            "totals": totals,
+        })
+        .to_string()
+    } else {
+        format!(
+            "Finished in {}, {}",
+            format_duration(summary.elapsed),
+            totals.join(", ")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::app::logging::RoutineLogger;
+    use crate::config::Config;
+    use crate::routine::{Routine, Task};
+
+    #[test]
+    fn quitting_writes_the_whole_log() {
+        let path = std::env::temp_dir().join(format!("accordion-signal-{}", std::process::id()));
+        let logger =
+            RoutineLogger::with_writer(io::BufWriter::new(std::fs::File::create(&path).unwrap()));
+        let mut app = App::with_routine(
+            Config::default(),
+            Routine::with_tasks(vec![Task::new("a", 60), Task::new("b", 60)]),
+            logger,
+        );
+        let (sender, receiver) = mpsc::channel();
+        let (reply, _) = mpsc::channel();
+        sender
+            .send(Event::Control(ControlRequest {
+                command: ControlCommand::Complete,
+                reply,
+            }))
+            .unwrap();
+        handle(&mut app, receiver.recv().unwrap());
+
+        request_quit(&sender);
+        handle(&mut app, receiver.recv().unwrap());
+        let log = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(app.should_quit);
+        assert!(log.contains("\ta \tcompleted"));
+    }
+
+    #[test]
+    fn answers_as_json() {
+        let line = answer_line(&Err("paused".to_string()), true);
+
+        assert_eq!(
+            line,
+            r#"{"message":"paused","ok":false,"type":"answer"}"#.to_string()
+        );
+    }
+}
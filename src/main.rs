@@ -22,31 +22,48 @@ pub mod cli;
 /// Config file
 pub mod config;
 
+/// Socket for driving a running routine from other programs.
+pub mod control;
+
+/// Running without a terminal interface.
+pub mod headless;
+
 use app::App;
 use clap::Parser;
 use cli::Cli;
 use color_eyre::Result;
+#[cfg(unix)]
+use control::ControlSocket;
 use event::{Event, EventHandler};
 use ratatui::{backend::CrosstermBackend, Terminal};
 use tui::Tui;
-use update::update;
+use update::{control, update};
 
 fn main() -> Result<()> {
     cli_log::init_cli_log!();
 
     let cli = Cli::parse();
 
-    if cli.run_instead_of_tui() {
+    if cli.run_instead_of_tui()? {
         return Ok(());
     }
 
+    let headless = cli.headless.then_some(cli.json);
+
     // Create an application.
-    let mut app = App::new(cli);
+    let mut app = App::new(cli)?;
+
+    if let Some(json) = headless {
+        return headless::run(app, json);
+    }
 
     // Initialize the terminal user interface.
     let backend = CrosstermBackend::new(std::io::stderr());
     let terminal = Terminal::new(backend)?;
     let events = EventHandler::new(500);
+    // kept until the end, when dropping it removes the socket
+    #[cfg(unix)]
+    let _control = ControlSocket::from_config(&app.config.control, events.sender())?;
     let mut tui = Tui::new(terminal, events);
     tui.enter()?;
 
@@ -55,9 +72,6 @@ fn main() -> Result<()> {
         // Render the user interface.
         tui.draw(&mut app)?;
         // Handle events.
-        // Explanation for allow: I intend for Mouse to stay empty, but intend
-        // for Resize to get filled in later.
-        #[allow(clippy::match_same_arms)]
         match tui.events.next()? {
             Event::Tick => app.tick(),
             Event::Key(key_event) => update(&mut app, key_event),
@@ -66,8 +80,10 @@ fn main() -> Result<()> {
                 // unless there are strong requests for it, because I
                 // don't like it.
             }
-            Event::Resize(_, _) => {
-                //todo!("must handle resizing before release!")
+            Event::Resize(width, height) => tui.resize(width, height)?,
+            Event::Control(request) => {
+                // the client may have given up waiting, which is fine
+                let _ = request.reply.send(control(&mut app, request.command));
             }
         };
     }
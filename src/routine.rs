@@ -1,5 +1,9 @@
+pub mod check;
 pub mod flex;
 pub mod parse;
+pub mod plan;
+pub mod playlist;
+pub mod repeat;
 pub mod task;
 
 pub use task::{CompletionStatus, Task};
@@ -7,9 +11,36 @@ pub use task::{CompletionStatus, Task};
 use std::cmp::max;
 use std::time::Duration;
 
-use chrono::{DateTime, Local};
+use chrono::{DateTime, Local, TimeDelta};
+use serde::Deserialize;
 
-use flex::{Flex, FlexItem};
+use flex::{Bounds, Flex, FlexItem};
+
+/// How progress through a routine is measured.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProgressMetric {
+    /// The share of the routine's planned time that is in finished tasks.
+    #[default]
+    TimeWeighted,
+    /// The share of the tasks that are finished.
+    TaskCount,
+    /// Time spent against the time the routine has, up to the deadline if there is one.
+    ElapsedVsGoal,
+}
+
+/// Whether `task` counts as finished for progress. Skipping is counted with `count_skipped`,
+/// for more accurate timing, or not, to discourage skipping. Breaks never count.
+fn finished(task: &Task, count_skipped: bool) -> bool {
+    if task.is_break {
+        return false;
+    }
+    match task.status {
+        CompletionStatus::NotYet => false,
+        CompletionStatus::Done => true,
+        CompletionStatus::Skipped => count_skipped,
+    }
+}
 
 #[derive(Debug, Copy, Clone)]
 pub enum ToggleFailure {
@@ -19,7 +50,13 @@ pub enum ToggleFailure {
 
 impl FlexItem for Task {
     fn min_size(&self) -> Duration {
-        self.elapsed
+        match self.status {
+            CompletionStatus::NotYet if self.rigid => max(self.elapsed, self.original_duration),
+            CompletionStatus::NotYet => {
+                max(self.elapsed, self.min_duration.min(self.original_duration))
+            }
+            _ => self.elapsed,
+        }
     }
     fn max_size(&self) -> Duration {
         match self.status {
@@ -51,12 +88,19 @@ pub struct Routine {
     /// The active task, if any.
     /// TODO this should probably eventually use an ID number.
     //active: Option<usize>,
-    /// Amount of time to try to fit tasks into.
+    /// Amount of time to try to fit tasks into. Without a deadline, the buffers between them
+    /// come on top of this.
     pub flex_goal: Duration,
     /// Timing Mode
     mode: TimeMode,
-    /// Time elapsed while not not focused on a task
+    /// Time elapsed while not not focused on a task, that no buffer could take
     spilled_time: Duration,
+    /// Time set aside for switching after each task but the last, unless the task has its
+    /// own.
+    buffer: Duration,
+    /// The buffer after each task, shrunk to fit like the tasks are. Kept by position, so
+    /// anything that adds, removes or moves a task refits them.
+    buffers: Vec<Duration>,
 }
 
 impl Routine {
@@ -73,9 +117,66 @@ impl Routine {
             spilled_time: Duration::ZERO,
             flex_goal: original_max,
             mode: TimeMode::ExpectedEnd,
+            buffer: Duration::ZERO,
+            buffers: vec![],
+        }
+    }
+
+    /// Sets the buffer between tasks that don't have their own.
+    pub fn set_buffer(&mut self, buffer: Duration) {
+        self.buffer = buffer;
+        self.update_flex();
+    }
+
+    /// The buffer planned after task `i`. There's none after the last task.
+    fn planned_buffer(&self, i: usize) -> Duration {
+        match self.tasks.get(i) {
+            Some(task) if i + 1 < self.tasks.len() => task.buffer.unwrap_or(self.buffer),
+            _ => Duration::ZERO,
         }
     }
 
+    /// How far the buffer after task `i` can shrink and grow. Once the next task has been
+    /// started, it's over, and stays at what was used of it.
+    fn buffer_bounds(&self, i: usize) -> Bounds {
+        let used = self
+            .tasks
+            .get(i)
+            .map_or(Duration::ZERO, |task| task.buffer_used);
+        let over = self
+            .tasks
+            .get(i + 1)
+            .is_none_or(|next| next.status != CompletionStatus::NotYet || !next.elapsed.is_zero());
+        Bounds {
+            min: used,
+            max: if over {
+                used
+            } else {
+                max(used, self.planned_buffer(i))
+            },
+        }
+    }
+
+    /// The buffer after task `i`, as shrunk to fit.
+    pub fn buffer(&self, i: usize) -> Duration {
+        self.buffers.get(i).copied().unwrap_or_default()
+    }
+
+    /// The time to fit tasks and buffers into.
+    fn goal(&self) -> Duration {
+        match self.mode {
+            TimeMode::ExpectedEnd => {
+                self.flex_goal + (0..self.tasks.len()).map(|i| self.planned_buffer(i)).sum()
+            }
+            TimeMode::FixedEnd(_) => self.flex_goal,
+        }
+    }
+
+    /// Time spent without a task that buffers took.
+    fn buffers_used(&self) -> Duration {
+        self.tasks.iter().map(|task| task.buffer_used).sum()
+    }
+
     // Not ideal to clone here but I'm only using it upon user input and routines
     // shouldn't be that long. Good enough for prototyping. TODO
     pub fn get_checkboxes(&self) -> Vec<bool> {
@@ -96,19 +197,101 @@ impl Routine {
         match self.mode {
             TimeMode::ExpectedEnd => (),
             TimeMode::FixedEnd(deadline) => {
-                // TODO handle past due case
-                let time_left = (deadline - now).to_std().expect("handle negative");
-                let time_spent = self.elapsed();
+                // Past the deadline, there is no time left to fit anything into.
+                let time_left = (deadline - now).to_std().unwrap_or(Duration::ZERO);
+                let time_spent = self.elapsed() + self.buffers_used();
                 self.flex_goal = time_spent + time_left;
                 self.update_flex();
             }
         }
     }
 
+    /// How much time there is to spare before the deadline: the time left, minus what the
+    /// unfinished tasks would still need at their original durations. `None` without a
+    /// deadline.
+    pub fn slack(&self, now: DateTime<Local>) -> Option<Duration> {
+        match self.mode {
+            TimeMode::ExpectedEnd => None,
+            TimeMode::FixedEnd(deadline) => {
+                let time_left = (deadline - now).to_std().unwrap_or(Duration::ZERO);
+                Some(time_left.saturating_sub(self.time_needed()))
+            }
+        }
+    }
+
+    /// How much of what the unfinished tasks still need at their original durations fits
+    /// before the deadline, from 0 to 1. `None` without a deadline.
+    pub fn compression(&self, now: DateTime<Local>) -> Option<f64> {
+        match self.mode {
+            TimeMode::ExpectedEnd => None,
+            TimeMode::FixedEnd(deadline) => {
+                let time_left = (deadline - now).to_std().unwrap_or(Duration::ZERO);
+                let needed = self.time_needed();
+                if needed.is_zero() {
+                    Some(1.0)
+                } else {
+                    Some(time_left.div_duration_f64(needed).min(1.0))
+                }
+            }
+        }
+    }
+
+    /// How far ahead of schedule the routine is, or behind if negative: the time there is
+    /// for the unfinished tasks, minus what they'd need at their original durations.
+    pub fn schedule_offset(&self, now: DateTime<Local>) -> TimeDelta {
+        let available = match self.mode {
+            TimeMode::ExpectedEnd => {
+                signed(self.goal())
+                    - signed(self.spilled_time + self.buffers_used() + self.elapsed())
+            }
+            TimeMode::FixedEnd(deadline) => deadline - now,
+        };
+        available - signed(self.time_needed())
+    }
+
+    /// What the unfinished tasks, and the buffers between them, still need at their original
+    /// durations.
+    fn time_needed(&self) -> Duration {
+        let tasks: Duration = self
+            .tasks
+            .iter()
+            .filter(|task| task.status == CompletionStatus::NotYet)
+            .map(|task| task.original_duration.saturating_sub(task.elapsed))
+            .sum();
+        let buffers: Duration = (0..self.tasks.len())
+            .map(|i| {
+                let bounds = self.buffer_bounds(i);
+                bounds.max.saturating_sub(bounds.min)
+            })
+            .sum();
+        tasks + buffers
+    }
+
+    pub fn deadline(&self) -> Option<DateTime<Local>> {
+        match self.mode {
+            TimeMode::ExpectedEnd => None,
+            TimeMode::FixedEnd(deadline) => Some(deadline),
+        }
+    }
+
+    /// Whether every task is done or skipped. An empty routine is never complete.
+    pub fn is_complete(&self) -> bool {
+        !self.tasks.is_empty()
+            && self
+                .tasks
+                .iter()
+                .all(|task| task.status != CompletionStatus::NotYet)
+    }
+
     pub fn set_deadline(&mut self, deadline: DateTime<Local>) {
-        self.mode = TimeMode::FixedEnd(deadline);
         // TODO put timing call in app module
-        self.sync_goal(Local::now());
+        self.set_deadline_at(deadline, Local::now());
+    }
+
+    /// Sets the deadline, fitting the tasks into the time from `now` until then.
+    pub fn set_deadline_at(&mut self, deadline: DateTime<Local>, now: DateTime<Local>) {
+        self.mode = TimeMode::FixedEnd(deadline);
+        self.sync_goal(now);
     }
 
     fn increase_time_for_new_task(&mut self, task: &Task) {
@@ -132,6 +315,75 @@ impl Routine {
         self.update_flex();
     }
 
+    /// Removes a task from the routine. Time already spent on it stays spent.
+    pub fn remove(&mut self, i: Option<usize>) -> Option<Task> {
+        let i = i.filter(|&i| i < self.tasks.len())?;
+        let task = self.tasks.remove(i);
+        self.spilled_time += task.elapsed + task.buffer_used;
+        match self.mode {
+            // Only the time the task had left comes off the goal, so the other tasks
+            // keep the same share they had before.
+            TimeMode::ExpectedEnd => {
+                let left = task.max_size().saturating_sub(task.elapsed);
+                self.flex_goal = self.flex_goal.saturating_sub(left);
+            }
+            // the deadline stays put, so the other tasks get the freed up time
+            TimeMode::FixedEnd(_) => (),
+        }
+        self.update_flex();
+        Some(task)
+    }
+
+    /// Puts a task taken out by [`Routine::remove`] back, undoing everything the removal
+    /// did.
+    pub fn restore(&mut self, i: usize, task: Task) {
+        self.spilled_time = self
+            .spilled_time
+            .saturating_sub(task.elapsed + task.buffer_used);
+        match self.mode {
+            TimeMode::ExpectedEnd => {
+                self.flex_goal += task.max_size().saturating_sub(task.elapsed);
+            }
+            TimeMode::FixedEnd(_) => (),
+        }
+        self.tasks.insert(i.min(self.tasks.len()), task);
+        self.update_flex();
+    }
+
+    /// Moves a task to another position, shifting the tasks in between.
+    pub fn move_task(&mut self, from: usize, to: usize) -> Result<(), ToggleFailure> {
+        if from >= self.tasks.len() || to >= self.tasks.len() {
+            return Err(ToggleFailure::NoSelection);
+        }
+        let task = self.tasks.remove(from);
+        self.tasks.insert(to, task);
+        self.update_flex();
+        Ok(())
+    }
+
+    /// Renames a task and changes its original duration, as if the routine file had said so
+    /// all along.
+    pub fn edit(
+        &mut self,
+        i: Option<usize>,
+        name: &str,
+        original_duration: Duration,
+    ) -> Result<(), ToggleFailure> {
+        let task = self.get_nth_mut(i).ok_or(ToggleFailure::NoSelection)?;
+        let old_duration = task.original_duration;
+        task.name = name.to_owned();
+        task.original_duration = original_duration;
+        match self.mode {
+            TimeMode::ExpectedEnd => {
+                self.flex_goal = (self.flex_goal + original_duration).saturating_sub(old_duration);
+            }
+            // same as for new tasks: the deadline stays put
+            TimeMode::FixedEnd(_) => (),
+        }
+        self.update_flex();
+        Ok(())
+    }
+
     /*
     pub fn get_current(&mut self) -> Option<&mut Task> {
         match self.active {
@@ -154,12 +406,75 @@ impl Routine {
         }
     }
 
+    /// Whether the tasks fit in the time there is for them, even shrunk as far as they can.
+    pub fn fits(&self) -> bool {
+        let buffers: Duration = (0..self.tasks.len())
+            .map(|i| self.buffer_bounds(i).min)
+            .sum();
+        self.goal().saturating_sub(self.spilled_time) >= self.min_size() + buffers
+    }
+
+    /// Shrinks optional tasks first, then the rest and the buffers between them down to their
+    /// minimums. Only if that isn't enough are rigid tasks and minimums shrunk too.
     fn update_flex(&mut self) {
-        let times = self
-            .flex(self.flex_goal - self.spilled_time)
-            .unwrap_or(vec![Duration::ZERO; self.tasks.len()]);
+        let size = self.goal().saturating_sub(self.spilled_time);
+        // each task followed by the buffer after it
+        let bounds = |limits: fn(&Task) -> Bounds, buffers_shrink: bool| -> Vec<Bounds> {
+            self.tasks
+                .iter()
+                .enumerate()
+                .flat_map(|(i, task)| {
+                    let buffer = self.buffer_bounds(i);
+                    let buffer = Bounds {
+                        min: if buffers_shrink {
+                            buffer.min
+                        } else {
+                            buffer.max
+                        },
+                        max: buffer.max,
+                    };
+                    [limits(task), buffer]
+                })
+                .collect()
+        };
+        let optional_first = bounds(
+            |task| Bounds {
+                min: if task.optional {
+                    task.min_size()
+                } else {
+                    task.max_size()
+                },
+                max: task.max_size(),
+            },
+            false,
+        );
+        let optional_gone = bounds(
+            |task| Bounds {
+                min: task.min_size(),
+                max: if task.optional {
+                    task.min_size()
+                } else {
+                    task.max_size()
+                },
+            },
+            true,
+        );
+        let anything = bounds(
+            |task| Bounds {
+                min: task.elapsed,
+                max: task.max_size(),
+            },
+            true,
+        );
+        let times = optional_first
+            .flex(size)
+            .or_else(|_| optional_gone.flex(size))
+            .or_else(|_| anything.flex(size))
+            .unwrap_or(vec![Duration::ZERO; 2 * self.tasks.len()]);
+        self.buffers = times.iter().skip(1).step_by(2).copied().collect();
         times
             .iter()
+            .step_by(2)
             .zip(self.tasks.iter_mut())
             .for_each(|(&time, task)| {
                 task.duration = time;
@@ -208,47 +523,127 @@ impl Routine {
         }
     }
 
+    /// Sets a task's status directly, without the toggling rules.
+    pub fn set_status(
+        &mut self,
+        i: Option<usize>,
+        status: CompletionStatus,
+    ) -> Result<(), ToggleFailure> {
+        let task = self.get_nth_mut(i).ok_or(ToggleFailure::NoSelection)?;
+        task.status = status;
+        self.update_flex();
+        Ok(())
+    }
+
+    /// The time planned for the tasks and the buffers between them.
     pub fn duration(&self) -> Duration {
-        self.tasks.iter().map(|task| task.duration).sum()
+        self.tasks
+            .iter()
+            .map(|task| task.duration)
+            .sum::<Duration>()
+            + self.buffers.iter().sum::<Duration>()
     }
 
     pub fn elapsed(&self) -> Duration {
         self.tasks.iter().map(|task| task.elapsed).sum()
     }
 
+    /// The original durations of the tasks, leaving out breaks.
     pub fn total_originals(&self) -> Duration {
-        self.tasks.iter().map(|task| task.original_duration).sum()
+        self.tasks
+            .iter()
+            .filter(|task| !task.is_break)
+            .map(|task| task.original_duration)
+            .sum()
     }
 
-    pub fn completed_originals(&self) -> Duration {
+    /// The original durations of the finished tasks, leaving out breaks. Skipped tasks count
+    /// as finished with `count_skipped`.
+    pub fn completed_originals(&self, count_skipped: bool) -> Duration {
         self.tasks
             .iter()
-            .filter(|task| match task.status {
-                CompletionStatus::NotYet => false,
-                // counting skipped tasks for percentage "complete" is a design
-                // decision. It may end up being something to make configurable.
-                // The benefit to true is more accurate timing and encouraging
-                // making it through the list. The benefit to
-                // false is gamification to discourage skipping. TODO
-                CompletionStatus::Done | CompletionStatus::Skipped => true,
-            })
+            .filter(|task| finished(task, count_skipped))
             .map(|task| task.original_duration)
             .sum()
     }
 
+    /// How far through the routine it is, from 0 to 1, measured by `metric`. Skipped tasks
+    /// count as finished with `count_skipped`.
+    pub fn progress(&self, metric: ProgressMetric, count_skipped: bool) -> f64 {
+        let ratio = match metric {
+            ProgressMetric::TimeWeighted => self
+                .completed_originals(count_skipped)
+                .div_duration_f64(self.total_originals()),
+            ProgressMetric::TaskCount => {
+                let finished = self
+                    .tasks
+                    .iter()
+                    .filter(|task| finished(task, count_skipped))
+                    .count();
+                let total = self.tasks.iter().filter(|task| !task.is_break).count();
+                let finished = u32::try_from(finished).unwrap_or(u32::MAX);
+                let total = u32::try_from(total).unwrap_or(u32::MAX);
+                f64::from(finished) / f64::from(total)
+            }
+            ProgressMetric::ElapsedVsGoal => {
+                (self.elapsed() + self.buffers_used() + self.spilled_time)
+                    .div_duration_f64(self.goal())
+            }
+        };
+        // an empty routine, or one with no time, divides by zero
+        if ratio.is_nan() {
+            0.0
+        } else {
+            ratio.min(1.0)
+        }
+    }
+
+    /// The time left for the tasks and the buffers between them.
     pub fn remaining(&self) -> Duration {
-        self.tasks.iter().map(task::Task::remaining).sum()
+        let buffers: Duration = self
+            .tasks
+            .iter()
+            .zip(&self.buffers)
+            .map(|(task, buffer)| buffer.saturating_sub(task.buffer_used))
+            .sum();
+        self.tasks
+            .iter()
+            .map(task::Task::remaining)
+            .sum::<Duration>()
+            + buffers
     }
 
+    /// Spends time on task `i`. Without a task, the time goes to the buffer before the first
+    /// unfinished task, as far as it lasts, and comes out of the time for the tasks after
+    /// that.
     pub fn elapse(&mut self, i: Option<usize>, duration: Duration) {
-        match i {
-            Some(i) => self.tasks[i].elapse(duration),
-            None => self.spilled_time += duration,
+        match self.get_nth_mut(i) {
+            Some(task) => task.elapse(duration),
+            None => {
+                let before = self
+                    .tasks
+                    .iter()
+                    .position(|task| task.status == CompletionStatus::NotYet)
+                    .and_then(|next| next.checked_sub(1));
+                let room = before.map_or(Duration::ZERO, |i| {
+                    let used = self.tasks.get(i).map_or(Duration::ZERO, |t| t.buffer_used);
+                    self.planned_buffer(i).saturating_sub(used)
+                });
+                let buffered = duration.min(room);
+                if let Some(task) = self.get_nth_mut(before) {
+                    task.buffer_used += buffered;
+                }
+                self.spilled_time += duration - buffered;
+            }
         }
         self.update_flex();
     }
 }
 
+fn signed(duration: Duration) -> TimeDelta {
+    TimeDelta::from_std(duration).unwrap_or(TimeDelta::MAX)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -404,6 +799,324 @@ mod tests {
         assert_eq!(list.elapsed(), Duration::new(80, 0))
     }
 
+    #[test]
+    fn edit_changes_goal() {
+        let mut list = Routine::default();
+        list.push(Task::new("a", 120));
+        list.push(Task::new("b", 60));
+
+        list.edit(Some(1), "c", Duration::new(30, 0)).unwrap();
+
+        assert_eq!(list.tasks[1].name, "c");
+        assert_eq!(list.flex_goal, Duration::new(150, 0));
+        assert_eq!(list.tasks[1].duration, Duration::new(30, 0));
+    }
+
+    #[test]
+    fn edit_keeps_deadline() {
+        let mut list = Routine::default();
+        list.push(Task::new("a", 120));
+        list.push(Task::new("b", 60));
+        list.set_deadline(Local::now() + Duration::new(90, 0));
+        let goal = list.flex_goal;
+
+        list.edit(Some(1), "b", Duration::new(600, 0)).unwrap();
+
+        assert_eq!(list.flex_goal, goal);
+        assert!(list.tasks[1].duration < Duration::new(600, 0));
+    }
+
+    #[test]
+    fn edit_without_selection() {
+        let mut list = Routine::default();
+        list.push(Task::new("a", 120));
+
+        assert!(list.edit(None, "b", Duration::new(60, 0)).is_err());
+    }
+
+    #[test]
+    fn remove_shrinks_goal() {
+        let mut list = Routine::default();
+        list.push(Task::new("a", 120));
+        list.push(Task::new("b", 60));
+
+        let removed = list.remove(Some(0)).unwrap();
+
+        assert_eq!(removed.name, "a");
+        assert_eq!(list.flex_goal, Duration::new(60, 0));
+        assert_eq!(list.tasks[0].duration, Duration::new(60, 0));
+    }
+
+    #[test]
+    fn remove_keeps_time_spent() {
+        let mut list = Routine::default();
+        list.push(Task::new("a", 120));
+        list.push(Task::new("b", 60));
+        list.push(Task::new("c", 60));
+        list.elapse(Some(0), Duration::new(150, 0));
+
+        list.remove(Some(0));
+
+        // the 30s of overtime still has to come out of the remaining tasks
+        assert_eq!(list.duration(), Duration::new(90, 0));
+    }
+
+    #[test]
+    fn remove_out_of_range() {
+        let mut list = Routine::default();
+        list.push(Task::new("a", 120));
+
+        assert!(list.remove(Some(1)).is_none());
+        assert_eq!(list.tasks.len(), 1);
+    }
+
+    #[test]
+    fn slack_before_deadline() {
+        let mut list = Routine::default();
+        list.push(Task::new("a", 120));
+        list.push(Task::new("b", 60));
+        let now = Local::now();
+        list.set_deadline(now + Duration::new(600, 0));
+        list.elapse(Some(0), Duration::new(20, 0));
+
+        assert_eq!(list.slack(now), Some(Duration::new(440, 0)));
+    }
+
+    #[test]
+    fn compression_before_deadline() {
+        let mut list = Routine::default();
+        list.push(Task::new("a", 120));
+        list.push(Task::new("b", 80));
+        let now = Local::now();
+        list.set_deadline(now + Duration::new(100, 0));
+
+        assert_eq!(list.compression(now), Some(0.5_f64));
+    }
+
+    #[test]
+    fn rigid_task_keeps_its_time() {
+        let mut list = Routine::default();
+        let mut rigid = Task::new("a", 60);
+        rigid.rigid = true;
+        list.push(rigid);
+        list.push(Task::new("b", 60));
+        let now = Local::now();
+        list.set_deadline_at(now + Duration::new(90, 0), now);
+
+        assert_eq!(list.tasks[0].duration, Duration::new(60, 0));
+        assert_eq!(list.tasks[1].duration, Duration::new(30, 0));
+    }
+
+    #[test]
+    fn time_without_a_task_comes_out_of_the_buffer() {
+        let mut list = Routine::default();
+        list.push(Task::new("a", 60));
+        list.push(Task::new("b", 60));
+        list.set_buffer(Duration::new(30, 0));
+        assert_eq!(list.duration(), Duration::new(150, 0));
+
+        list.elapse(Some(0), Duration::new(60, 0));
+        list.toggle(Some(0)).unwrap();
+        list.elapse(None, Duration::new(20, 0));
+        assert_eq!(list.tasks[1].duration, Duration::new(60, 0));
+        assert_eq!(list.remaining(), Duration::new(70, 0));
+
+        list.elapse(None, Duration::new(20, 0));
+        assert_eq!(list.tasks[1].duration, Duration::new(50, 0));
+        assert_eq!(list.buffer(0), Duration::new(30, 0));
+    }
+
+    #[test]
+    fn buffers_shrink_with_the_tasks() {
+        let mut list = Routine::default();
+        list.push(Task::new("a", 60));
+        let mut b = Task::new("b", 60);
+        b.buffer = Some(Duration::new(120, 0));
+        list.push(b);
+        list.set_buffer(Duration::new(60, 0));
+        let now = Local::now();
+        list.set_deadline_at(now + Duration::new(150, 0), now);
+
+        assert_eq!(list.tasks[0].duration, Duration::new(50, 0));
+        assert_eq!(list.buffer(0), Duration::new(50, 0));
+        // nothing comes after the last task, so there's no buffer after it
+        assert_eq!(list.buffer(1), Duration::ZERO);
+
+        // b's own buffer goes with it, and a is last now
+        list.move_task(1, 0).unwrap();
+        assert_eq!(list.buffer(1), Duration::ZERO);
+        assert_eq!(
+            list.tasks[0].duration + list.buffer(0) + list.tasks[1].duration,
+            Duration::new(150, 0)
+        );
+        assert_eq!(list.buffer(0), Duration::new(75, 0));
+    }
+
+    #[test]
+    fn breaks_dont_count_toward_progress() {
+        let mut list = Routine::default();
+        list.push(Task::new("a", 60));
+        let mut rest = Task::new("break", 60);
+        rest.is_break = true;
+        list.push(rest);
+        list.push(Task::new("b", 60));
+        list.toggle(Some(0)).unwrap();
+        list.toggle(Some(1)).unwrap();
+
+        assert_eq!(list.completed_originals(true), Duration::new(60, 0));
+        assert_eq!(list.progress(ProgressMetric::TimeWeighted, true), 0.5_f64);
+        assert_eq!(list.progress(ProgressMetric::TaskCount, true), 0.5_f64);
+    }
+
+    #[test]
+    fn optional_task_shrinks_first() {
+        let mut list = Routine::default();
+        let mut optional = Task::new("a", 60);
+        optional.optional = true;
+        list.push(optional);
+        let mut b = Task::new("b", 60);
+        b.min_duration = Duration::new(50, 0);
+        list.push(b);
+        let now = Local::now();
+        list.set_deadline_at(now + Duration::new(90, 0), now);
+
+        assert_eq!(list.tasks[0].duration, Duration::new(30, 0));
+        assert_eq!(list.tasks[1].duration, Duration::new(60, 0));
+
+        list.set_deadline_at(now + Duration::new(55, 0), now);
+
+        assert_eq!(list.tasks[0].duration, Duration::ZERO);
+        assert_eq!(list.tasks[1].duration, Duration::new(55, 0));
+    }
+
+    #[test]
+    fn progress_metrics() {
+        let mut list = Routine::default();
+        list.push(Task::new("a", 60));
+        list.push(Task::new("b", 60));
+        list.push(Task::new("c", 120));
+        list.elapse(Some(0), Duration::new(30, 0));
+        list.toggle(Some(0)).unwrap();
+        list.skip(Some(1)).unwrap();
+
+        assert_eq!(list.progress(ProgressMetric::TimeWeighted, true), 0.5_f64);
+        assert_eq!(list.progress(ProgressMetric::TimeWeighted, false), 0.25_f64);
+        assert_eq!(
+            list.progress(ProgressMetric::TaskCount, false),
+            1.0_f64 / 3.0_f64
+        );
+        // 30s spent of the 4m planned
+        assert_eq!(
+            list.progress(ProgressMetric::ElapsedVsGoal, false),
+            0.125_f64
+        );
+        assert_eq!(
+            Routine::default().progress(ProgressMetric::TaskCount, true),
+            0.0_f64
+        );
+    }
+
+    #[test]
+    fn behind_after_overtime() {
+        let mut list = Routine::default();
+        list.push(Task::new("a", 60));
+        list.push(Task::new("b", 60));
+        list.elapse(Some(0), Duration::new(90, 0));
+
+        assert_eq!(list.schedule_offset(Local::now()), TimeDelta::seconds(-30));
+    }
+
+    #[test]
+    fn ahead_after_finishing_early() {
+        let mut list = Routine::default();
+        list.push(Task::new("a", 60));
+        list.push(Task::new("b", 60));
+        list.elapse(Some(0), Duration::new(20, 0));
+        list.toggle(Some(0)).unwrap();
+
+        assert_eq!(list.schedule_offset(Local::now()), TimeDelta::seconds(40));
+    }
+
+    #[test]
+    fn no_slack_without_deadline() {
+        let mut list = Routine::default();
+        list.push(Task::new("a", 120));
+
+        assert_eq!(list.slack(Local::now()), None);
+    }
+
+    #[test]
+    fn complete_when_all_done_or_skipped() {
+        let mut list = Routine::default();
+        list.push(Task::new("a", 120));
+        list.push(Task::new("b", 60));
+
+        list.toggle(Some(0)).unwrap();
+        assert!(!list.is_complete());
+        list.skip(Some(1)).unwrap();
+        assert!(list.is_complete());
+    }
+
+    #[test]
+    fn empty_is_not_complete() {
+        assert!(!Routine::default().is_complete());
+    }
+
+    #[test]
+    fn restore_undoes_remove() {
+        let mut list = Routine::default();
+        list.push(Task::new("a", 120));
+        list.push(Task::new("b", 60));
+        list.elapse(Some(0), Duration::new(30, 0));
+        let goal = list.flex_goal;
+
+        let task = list.remove(Some(0)).unwrap();
+        list.restore(0, task);
+
+        assert_eq!(list.flex_goal, goal);
+        assert_eq!(list.spilled_time, Duration::ZERO);
+        assert_eq!(list.tasks[0].name, "a");
+        assert_eq!(list.tasks[0].elapsed, Duration::new(30, 0));
+    }
+
+    #[test]
+    fn move_task_down() {
+        let mut list = Routine::default();
+        list.push(Task::new("a", 120));
+        list.push(Task::new("b", 60));
+        list.push(Task::new("c", 60));
+
+        list.move_task(0, 1).unwrap();
+
+        let names: Vec<&str> = list.tasks.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, ["b", "a", "c"]);
+    }
+
+    #[test]
+    fn move_last_task_gets_a_buffer() {
+        let mut list = Routine::default();
+        list.push(Task::new("a", 60));
+        let mut b = Task::new("b", 60);
+        b.buffer = Some(Duration::new(90, 0));
+        list.push(b);
+        list.set_buffer(Duration::new(30, 0));
+        assert_eq!(list.goal(), Duration::new(150, 0));
+
+        list.move_task(1, 0).unwrap();
+
+        assert_eq!(list.buffer(0), Duration::new(90, 0));
+        assert_eq!(list.buffer(1), Duration::ZERO);
+        assert_eq!(list.goal(), Duration::new(210, 0));
+    }
+
+    #[test]
+    fn move_task_out_of_range() {
+        let mut list = Routine::default();
+        list.push(Task::new("a", 120));
+
+        assert!(list.move_task(0, 1).is_err());
+    }
+
     #[test]
     fn total_duration() {
         let mut list = Routine::default();
@@ -0,0 +1,367 @@
+// Finds every problem in a routine file at once, with line numbers, instead of stopping at
+// the first one like loading does. A playlist is checked along with every routine it lists.
+
+use color_eyre::eyre::Result;
+use csv::Trim;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use super::parse::{COLUMNS, OPTIONAL_COLUMNS};
+use super::playlist::{self, is_playlist};
+use super::repeat::{self, Expander};
+use crate::routine::task::duration::{parse_duration, DurationUnit};
+
+#[derive(Debug, PartialEq)]
+pub struct Problem {
+    pub line: u64,
+    pub message: String,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+/// # Errors
+///
+/// Will return an error if the file can't be opened. Problems with what's in it are returned
+/// as [`Problem`]s instead.
+pub fn check_csv(file_path: impl AsRef<Path>, default_unit: DurationUnit) -> Result<Vec<Problem>> {
+    Ok(check(File::open(file_path)?, default_unit))
+}
+
+/// Every problem in the routine or playlist at `path`, with the file each one is in. A
+/// playlist's problems come before those of the routines it lists.
+///
+/// # Errors
+///
+/// Will return an error if the file at `path` can't be read. A routine a playlist lists that
+/// can't be opened is a problem with the playlist instead.
+pub fn check_file(path: &Path, default_unit: DurationUnit) -> Result<Vec<(PathBuf, Problem)>> {
+    if !is_playlist(path) {
+        let problems = check_csv(path, default_unit)?;
+        return Ok(problems
+            .into_iter()
+            .map(|problem| (path.to_path_buf(), problem))
+            .collect());
+    }
+    let text = fs::read_to_string(path)?;
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let mut problems = vec![];
+    let mut routines = vec![];
+    let mut problem = |line: u64, message: String| {
+        problems.push((path.to_path_buf(), Problem { line, message }));
+    };
+    for (line, entry) in playlist::entries(&text) {
+        let routine = dir.join(entry);
+        if is_playlist(&routine) {
+            let itself =
+                routine == path || fs::canonicalize(&routine).ok() == fs::canonicalize(path).ok();
+            if itself {
+                problem(line, "the playlist lists itself".to_string());
+            } else {
+                problem(
+                    line,
+                    format!("lists another playlist, {entry}; playlists can only list routines"),
+                );
+            }
+            continue;
+        }
+        match check_csv(&routine, default_unit) {
+            Ok(found) => routines.extend(found.into_iter().map(|found| (routine.clone(), found))),
+            Err(e) => problem(line, format!("can't open {entry}: {e}")),
+        }
+    }
+    if playlist::entries(&text).next().is_none() {
+        problem(1, "no routines".to_string());
+    }
+    problems.extend(routines);
+    Ok(problems)
+}
+
+/// Every problem in a routine, in order. A bare number for a duration is in `default_unit`.
+pub fn check(routine: impl Read, default_unit: DurationUnit) -> Vec<Problem> {
+    let mut rdr = csv::ReaderBuilder::new()
+        .delimiter(b',')
+        .trim(Trim::All)
+        .comment(Some(b'#'))
+        // rows with missing or extra fields are reported here, rather than stopping the reader
+        .flexible(true)
+        .from_reader(routine);
+    let mut problems = vec![];
+    let mut problem = |line: u64, message: String| problems.push(Problem { line, message });
+
+    let headers = match rdr.headers() {
+        Ok(headers) => headers.clone(),
+        Err(e) => {
+            problem(1, format!("can't read the header: {e}"));
+            return problems;
+        }
+    };
+    let columns = headers.len();
+    let header_line = headers.position().map_or(1, csv::Position::line);
+    for (i, column) in headers.iter().enumerate() {
+        match COLUMNS.get(i) {
+            Some(expected) if column.eq_ignore_ascii_case(expected) => (),
+            Some(expected) => problem(
+                header_line,
+                format!(
+                    "column {} should be \"{expected}\", not \"{column}\"",
+                    i + 1
+                ),
+            ),
+            None if OPTIONAL_COLUMNS
+                .iter()
+                .any(|optional| column.eq_ignore_ascii_case(optional)) => {}
+            None => problem(header_line, format!("unknown column \"{column}\"")),
+        }
+    }
+    if columns < COLUMNS.len() {
+        problem(
+            header_line,
+            format!(
+                "missing columns; the header should be {}",
+                COLUMNS.join(",")
+            ),
+        );
+    }
+
+    let buffer_column = headers
+        .iter()
+        .position(|column| column.eq_ignore_ascii_case("buffer"));
+    let mut seen: HashMap<String, u64> = HashMap::new();
+    // only tracks which repeat blocks are open, so no tasks go in
+    let mut blocks = Expander::default();
+    let mut tasks = 0_usize;
+    for result in rdr.records() {
+        let record = match result {
+            Ok(record) => record,
+            Err(e) => {
+                let line = e.position().map_or(0, csv::Position::line);
+                problem(line, format!("can't read the row: {e}"));
+                continue;
+            }
+        };
+        let line = record.position().map_or(0, csv::Position::line);
+        let directive = repeat::directive(
+            record.get(0).unwrap_or_default(),
+            record.get(1).unwrap_or_default(),
+        );
+        if let Some(directive) = directive {
+            if let Err(e) = directive.and_then(|directive| blocks.directive(directive, line)) {
+                problem(line, e);
+            }
+            continue;
+        }
+        tasks += 1;
+        match record.get(0).filter(|name| !name.is_empty()) {
+            None => problem(line, "missing task name".to_string()),
+            Some(name) => {
+                if let Some(first) = seen.get(name) {
+                    problem(
+                        line,
+                        format!("duplicate task name \"{name}\", first on line {first}"),
+                    );
+                } else {
+                    seen.insert(name.to_string(), line);
+                }
+            }
+        }
+        let duration = match record.get(1).filter(|duration| !duration.is_empty()) {
+            None => {
+                problem(line, "missing duration".to_string());
+                None
+            }
+            Some(duration) => match parse_duration(duration, default_unit) {
+                Err(e) => {
+                    problem(line, format!("can't read duration \"{duration}\": {e}"));
+                    None
+                }
+                Ok(0) => {
+                    problem(line, "duration is zero".to_string());
+                    None
+                }
+                Ok(seconds) => Some((duration, seconds)),
+            },
+        };
+        let buffer = buffer_column.and_then(|i| record.get(i));
+        if let Some(buffer) = buffer.filter(|buffer| !buffer.is_empty()) {
+            match (parse_duration(buffer, default_unit), duration) {
+                (Err(e), _) => problem(line, format!("can't read buffer \"{buffer}\": {e}")),
+                // more slack than task is most likely the two columns swapped
+                (Ok(slack), Some((duration, seconds))) if slack > seconds => problem(
+                    line,
+                    format!(
+                        "buffer \"{buffer}\" is longer than the task's duration \"{duration}\""
+                    ),
+                ),
+                (Ok(_), _) => (),
+            }
+        }
+        if record.len() > columns.max(COLUMNS.len()) {
+            problem(
+                line,
+                format!(
+                    "{} fields, but only {} columns; quote names with commas in them",
+                    record.len(),
+                    columns
+                ),
+            );
+        }
+    }
+    if let Err((line, e)) = blocks.finish() {
+        problem(line, e);
+    }
+    if tasks == 0 {
+        problem(1, "no tasks".to_string());
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(routine: &str) -> Vec<String> {
+        check(routine.as_bytes(), DurationUnit::Minutes)
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn fine_routine() {
+        assert!(messages("task,duration\nshower,10m\n# comment\nbreakfast,15m\n").is_empty());
+        assert!(messages("task,duration,checklist,notes\npack,5m,laptop;badge,\n").is_empty());
+        assert!(messages("task,duration,buffer\npack,5m,1m\nleave,1m,\n").is_empty());
+    }
+
+    #[test]
+    fn bad_buffer() {
+        assert_eq!(
+            messages("task,duration,buffer\npack,5m,1m\nleave,1m,soon\n"),
+            vec!["line 3: can't read buffer \"soon\": \"soon\" isn't a number"]
+        );
+    }
+
+    #[test]
+    fn buffer_longer_than_its_task() {
+        assert_eq!(
+            messages("task,duration,buffer\npack,5m,10m\nleave,1m,1m\nwave,bye,10m\n"),
+            vec![
+                "line 2: buffer \"10m\" is longer than the task's duration \"5m\"",
+                "line 4: can't read duration \"bye\": \"bye\" isn't a number",
+            ]
+        );
+    }
+
+    #[test]
+    fn every_problem_with_its_line() {
+        let routine = "task,duration,priority\nshower,10m\nshower,5m\nbrush teeth,5 minutes\n,1m\nnap,0s\nstretch\n";
+
+        assert_eq!(
+            messages(routine),
+            vec![
+                "line 1: unknown column \"priority\"",
+                "line 3: duplicate task name \"shower\", first on line 2",
+                "line 4: can't read duration \"5 minutes\": unknown unit \"minutes\"; use h, m or s",
+                "line 5: missing task name",
+                "line 6: duration is zero",
+                "line 7: missing duration",
+            ]
+        );
+    }
+
+    #[test]
+    fn extra_fields() {
+        assert_eq!(
+            messages("task,duration\nwash, dry,10m\n"),
+            vec![
+                "line 2: can't read duration \"dry\": \"dry\" isn't a number",
+                "line 2: 3 fields, but only 2 columns; quote names with commas in them",
+            ]
+        );
+    }
+
+    #[test]
+    fn repeat_blocks() {
+        assert!(messages("task,duration\nrepeat Ana; Ben,\nshoes,1m\nend repeat,\n").is_empty());
+        assert_eq!(
+            messages("task,duration\nend repeat,\nrepeat many,\nrepeat 2,\nrest,30s\n"),
+            vec![
+                "line 2: \"end repeat\" without a \"repeat\" before it",
+                "line 3: repeat a block 1 to 99 times, like \"repeat 3\", or once for each name, like \"repeat Ana; Ben\"",
+                "line 4: \"repeat\" without an \"end repeat\"",
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_routine() {
+        assert_eq!(messages("task,duration\n"), vec!["line 1: no tasks"]);
+    }
+
+    #[test]
+    fn playlists_and_what_they_list() {
+        let dir = std::env::temp_dir().join(format!("accordion-check-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let playlist = dir.join("evening.playlist");
+        fs::write(
+            &playlist,
+            "# after dinner\ncleanup.csv\nevening.playlist\nmorning.playlist\nmissing.csv\n",
+        )
+        .unwrap();
+        fs::write(dir.join("cleanup.csv"), "task,duration\ndishes,0m\n").unwrap();
+
+        let problems = check_file(&playlist, DurationUnit::Minutes).unwrap();
+        let empty = dir.join("empty.playlist");
+        fs::write(&empty, "# nothing yet\n").unwrap();
+        let empty_problems = check_file(&empty, DurationUnit::Minutes).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        let problems: Vec<(PathBuf, String)> = problems
+            .into_iter()
+            .map(|(path, problem)| (path, problem.to_string()))
+            .collect();
+        assert_eq!(problems.len(), 4);
+        assert_eq!(
+            problems[..2],
+            [
+                (
+                    playlist.clone(),
+                    "line 3: the playlist lists itself".to_string()
+                ),
+                (
+                    playlist.clone(),
+                    "line 4: lists another playlist, morning.playlist; playlists can only list routines"
+                        .to_string()
+                ),
+            ]
+        );
+        assert_eq!(problems[2].0, playlist);
+        assert!(problems[2]
+            .1
+            .starts_with("line 5: can't open missing.csv: "));
+        assert_eq!(
+            problems[3],
+            (
+                dir.join("cleanup.csv"),
+                "line 2: duration is zero".to_string()
+            )
+        );
+        assert_eq!(
+            empty_problems,
+            [(
+                empty,
+                Problem {
+                    line: 1,
+                    message: "no routines".to_string()
+                }
+            )]
+        );
+    }
+}
@@ -10,6 +10,28 @@ pub trait FlexItem {
     fn min_size(&self) -> Duration;
 }
 
+/// Sizes given outright, for flexing items within other limits than their own.
+#[derive(Debug, Copy, Clone)]
+pub struct Bounds {
+    pub min: Duration,
+    pub max: Duration,
+}
+
+impl FlexItem for Bounds {
+    fn max_size(&self) -> Duration {
+        self.max
+    }
+    fn min_size(&self) -> Duration {
+        self.min
+    }
+}
+
+impl Flex for Vec<Bounds> {
+    fn get_items(&self) -> &Vec<impl FlexItem> {
+        self
+    }
+}
+
 pub trait Flex {
     fn get_items(&self) -> &Vec<impl FlexItem>;
 
@@ -36,7 +58,7 @@ pub trait Flex {
             // TODO better way to fail?
             return Err(self.min_size());
         }
-        if size > self.max_size() {
+        if size >= self.max_size() {
             return Ok(self.max_sizes());
         }
         let ratio = wiggle_room.div_duration_f64(shrinkable);
@@ -52,6 +74,8 @@ pub trait Flex {
 }
 
 #[cfg(test)]
+// Reason for allow: the float literals in these tests are only ever f64.
+#[allow(clippy::default_numeric_fallback)]
 mod tests {
     use super::*;
 
@@ -90,7 +114,7 @@ mod tests {
     }
 
     impl Flex for List {
-        fn get_items(&self) -> &Vec<Amount> {
+        fn get_items(&self) -> &Vec<impl FlexItem> {
             &self.items
         }
     }
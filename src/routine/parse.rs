@@ -1,45 +1,299 @@
+use color_eyre::eyre::{eyre, OptionExt, Result, WrapErr};
 use csv::{StringRecord, Trim};
-use std::{env, error::Error, ffi::OsString, fs::File};
+use std::{fs::File, path::Path, time::Duration};
 
-use super::Task;
+use super::{
+    repeat::{self, Expander, Row},
+    task::ChecklistItem,
+    Task,
+};
 
 // TODO what's a better way to specify this path?
-use crate::routine::task::parse_new::parse_duration;
+use crate::routine::task::duration::{format_duration_compact, parse_duration, DurationUnit};
 
-fn run() -> Result<Vec<Task>, Box<dyn Error>> {
-    let file_path = get_first_arg()?;
+/// The columns of a routine file, in order.
+pub const COLUMNS: [&str; 2] = ["task", "duration"];
+/// Columns that may follow the required ones, in any order.
+pub const OPTIONAL_COLUMNS: [&str; 3] = ["notes", "checklist", "buffer"];
+/// Separates the items in the checklist column.
+pub const CHECKLIST_SEPARATOR: char = ';';
+
+/// Where the optional columns are, if they're there.
+#[derive(Debug, Default, Clone, Copy)]
+struct Optional {
+    notes: Option<usize>,
+    checklist: Option<usize>,
+    buffer: Option<usize>,
+}
+
+impl Optional {
+    fn find(headers: &StringRecord) -> Self {
+        let find = |name: &str| {
+            headers
+                .iter()
+                .position(|header| header.eq_ignore_ascii_case(name))
+        };
+        Self {
+            notes: find("notes"),
+            checklist: find("checklist"),
+            buffer: find("buffer"),
+        }
+    }
+}
+
+/// A bare number for a duration is in `default_unit`. Repeated blocks are copied out into
+/// separate tasks.
+///
+/// # Errors
+///
+/// Will return an error if the file can't be read, or if any record in it isn't a valid task.
+pub fn read_csv(file_path: impl AsRef<Path>, default_unit: DurationUnit) -> Result<Vec<Task>> {
     let file = File::open(file_path)?;
     // Build the CSV reader and iterate over each record.
     let mut rdr = csv::ReaderBuilder::new()
         .delimiter(b',')
         .trim(Trim::All)
         .comment(Some(b'#'))
+        // the optional columns can be left off the end of a row
+        .flexible(true)
         .from_reader(file);
-    let mut tasks = Vec::<Task>::new();
+    let headers = rdr.headers()?;
+    let columns = headers.len();
+    let optional = Optional::find(headers);
+    let mut tasks = Expander::default();
     for result in rdr.records() {
         // The iterator yields Result<StringRecord, Error>, so we check the
         // error here.
         let record = result?;
-        tasks.push(parse_task(&record));
+        let line = record.position().map_or(0, csv::Position::line);
+        if record.len() > columns {
+            return Err(eyre!(
+                "Line {line} of the routine has {} fields, but there are only {columns} columns",
+                record.len()
+            ));
+        }
+        let name = record.get(0).unwrap_or_default();
+        let duration = record.get(1).unwrap_or_default();
+        match repeat::directive(name, duration) {
+            Some(directive) => directive
+                .and_then(|directive| tasks.directive(directive, line))
+                .map_err(|e| eyre!("Line {line} of the routine: {e}"))?,
+            None => tasks.task(
+                parse_task(&record, optional, default_unit)
+                    .wrap_err_with(|| format!("Line {line} of the routine"))?,
+            ),
+        }
     }
-    Ok(tasks)
+    tasks
+        .finish()
+        .map_err(|(line, e)| eyre!("Line {line} of the routine: {e}"))
 }
 
-fn get_first_arg() -> Result<OsString, Box<dyn Error>> {
-    match env::args_os().nth(1) {
-        None => Err(From::from("expected 1 argument, but got none")),
-        Some(file_path) => Ok(file_path),
+/// Writes tasks in the format [`read_csv`] reads, keeping their names, original durations,
+/// notes, checklists and buffers. Tasks copied out of a repeated block are written back as
+/// the block. The optional columns are only written if some task has them.
+///
+/// # Errors
+///
+/// Will return an error if the file can't be written.
+pub fn write_csv(file_path: impl AsRef<Path>, tasks: &[Task]) -> Result<()> {
+    let extras = tasks
+        .iter()
+        .any(|task| task.note.is_some() || !task.checklist.is_empty());
+    let buffers = tasks.iter().any(|task| task.buffer.is_some());
+    let mut wtr = csv::Writer::from_path(file_path)?;
+    let mut headers = COLUMNS.to_vec();
+    if extras {
+        headers.extend(["notes", "checklist"]);
+    }
+    if buffers {
+        headers.push("buffer");
     }
+    let width = headers.len();
+    wtr.write_record(headers)?;
+    for row in repeat::collapse(tasks) {
+        let (task, name) = match row {
+            Row::Task(task, name) => (task, name),
+            Row::Repeat(name) => {
+                wtr.write_record(directive_record(&name, width))?;
+                continue;
+            }
+            Row::End => {
+                wtr.write_record(directive_record(repeat::END, width))?;
+                continue;
+            }
+        };
+        let mut record = vec![
+            name.to_string(),
+            format_duration_compact(task.original_duration.as_secs()),
+        ];
+        if extras {
+            let checklist: Vec<&str> = task
+                .checklist
+                .iter()
+                .map(|item| item.text.as_str())
+                .collect();
+            record.push(task.note.clone().unwrap_or_default());
+            record.push(checklist.join(&format!("{CHECKLIST_SEPARATOR} ")));
+        }
+        if buffers {
+            record.push(
+                task.buffer
+                    .map(|buffer| format_duration_compact(buffer.as_secs()))
+                    .unwrap_or_default(),
+            );
+        }
+        wtr.write_record(record)?;
+    }
+    wtr.flush()?;
+    Ok(())
 }
 
-pub fn read_csv() -> Result<Vec<Task>, Box<dyn Error>> {
-    run()
+/// A `repeat` or `end repeat` row, with every other field empty.
+fn directive_record(name: &str, width: usize) -> Vec<&str> {
+    let mut record = vec![name];
+    record.resize(width, "");
+    record
 }
 
-fn parse_task(record: &StringRecord) -> Task {
-    Task::new(
-        record.get(0).expect("Missing CSV field."),
-        parse_duration(record.get(1).expect("Missing CSV field."))
-            .expect("Failure parsing duration. Format: _h_m_s"),
-    )
+fn parse_task(
+    record: &StringRecord,
+    optional: Optional,
+    default_unit: DurationUnit,
+) -> Result<Task> {
+    let name = record.get(0).ok_or_eyre("Missing CSV field.")?;
+    let duration = record.get(1).ok_or_eyre("Missing CSV field.")?;
+    let duration = parse_duration(duration, default_unit)
+        .map_err(|e| eyre!("Can't read duration {duration:?}: {e}"))?;
+    let field = |column: Option<usize>| {
+        column
+            .and_then(|i| record.get(i))
+            .filter(|field| !field.is_empty())
+    };
+    let mut task = Task::new(name, duration);
+    task.note = field(optional.notes).map(str::to_string);
+    task.checklist = field(optional.checklist)
+        .map(parse_checklist)
+        .unwrap_or_default();
+    if let Some(buffer) = field(optional.buffer) {
+        let secs = parse_duration(buffer, default_unit)
+            .map_err(|e| eyre!("Can't read buffer {buffer:?}: {e}"))?;
+        task.buffer = Some(Duration::from_secs(secs));
+    }
+    Ok(task)
+}
+
+fn parse_checklist(field: &str) -> Vec<ChecklistItem> {
+    field
+        .split(CHECKLIST_SEPARATOR)
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(ChecklistItem::new)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_then_read() {
+        let path = std::env::temp_dir().join(format!("accordion-write-{}", std::process::id()));
+        let tasks = vec![Task::new("start music", 90), Task::new("brush, teeth", 180)];
+
+        write_csv(&path, &tasks).unwrap();
+        let read = read_csv(&path, DurationUnit::Minutes).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(read.len(), 2);
+        assert_eq!(read[1].name, "brush, teeth");
+        assert_eq!(read[0].original_duration, Duration::from_secs(90));
+    }
+
+    #[test]
+    fn repeated_block() {
+        let path = std::env::temp_dir().join(format!("accordion-repeat-{}", std::process::id()));
+        std::fs::write(
+            &path,
+            "task,duration\nrepeat 2,\npushups,1m\nrest,30s\nend repeat,\nstretch,5m\n",
+        )
+        .unwrap();
+
+        let read = read_csv(&path, DurationUnit::Minutes).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let names: Vec<&str> = read.iter().map(|task| task.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "pushups (1/2)",
+                "rest (1/2)",
+                "pushups (2/2)",
+                "rest (2/2)",
+                "stretch"
+            ]
+        );
+        assert_eq!(read[3].original_duration, Duration::from_secs(30));
+    }
+
+    #[test]
+    fn repeated_block_written_back() {
+        let path =
+            std::env::temp_dir().join(format!("accordion-repeat-back-{}", std::process::id()));
+        let routine = "task,duration\nwarm up,5m\nrepeat Ana; Ben,\nshoes,1m\nrepeat 2,\nlace,10s\nend repeat,\nend repeat,\nrepeat Cy;,\ncoat,1m\nend repeat,\n";
+        std::fs::write(&path, routine).unwrap();
+
+        let mut read = read_csv(&path, DurationUnit::Minutes).unwrap();
+        write_csv(&path, &read).unwrap();
+        let written = std::fs::read_to_string(&path).unwrap();
+        // the block is written from its first copy, so changes to the others aren't kept
+        read[1].original_duration = Duration::from_secs(120);
+        read[4].original_duration = Duration::from_secs(180);
+        write_csv(&path, &read).unwrap();
+        let changed = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(read.len(), 8);
+        assert_eq!(written, routine);
+        assert_eq!(changed, routine.replace("shoes,1m", "shoes,2m"));
+    }
+
+    #[test]
+    fn notes_and_checklist() {
+        let path = std::env::temp_dir().join(format!("accordion-notes-{}", std::process::id()));
+        std::fs::write(
+            &path,
+            "task,duration,checklist,notes\npack bag,5m,laptop; charger;badge;,by the door\nshower,10m\n",
+        )
+        .unwrap();
+
+        let read = read_csv(&path, DurationUnit::Minutes).unwrap();
+        write_csv(&path, &read).unwrap();
+        let written = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(read[0].note.as_deref(), Some("by the door"));
+        let items: Vec<&str> = read[0].checklist.iter().map(|i| i.text.as_str()).collect();
+        assert_eq!(items, ["laptop", "charger", "badge"]);
+        assert!(read[1].note.is_none() && read[1].checklist.is_empty());
+        assert_eq!(
+            written,
+            "task,duration,notes,checklist\npack bag,5m,by the door,laptop; charger; badge\nshower,10m,,\n"
+        );
+    }
+
+    #[test]
+    fn buffer_column() {
+        let path = std::env::temp_dir().join(format!("accordion-buffer-{}", std::process::id()));
+        std::fs::write(&path, "task,duration,buffer\nshower,10m,2m\ndress,5m\n").unwrap();
+
+        let read = read_csv(&path, DurationUnit::Minutes).unwrap();
+        write_csv(&path, &read).unwrap();
+        let written = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(read[0].buffer, Some(Duration::from_secs(120)));
+        assert_eq!(read[1].buffer, None);
+        assert_eq!(written, "task,duration,buffer\nshower,10m,2m\ndress,5m,\n");
+    }
 }
@@ -0,0 +1,174 @@
+// The timeline a routine would follow if started now, for looking ahead without running it.
+
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::fmt::Write;
+use std::time::Duration;
+
+use super::Routine;
+use crate::ui::format_duration;
+
+#[derive(Debug, Serialize)]
+pub struct PlannedTask {
+    pub name: String,
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+    /// Seconds, after shrinking to fit the deadline.
+    pub duration: u64,
+    /// Seconds, as written in the routine.
+    pub original: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Plan {
+    pub routine: String,
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+    pub deadline: Option<DateTime<Local>>,
+    /// How much of their original durations the tasks get, from 0 to 1.
+    pub compression: f64,
+    pub tasks: Vec<PlannedTask>,
+    pub warnings: Vec<String>,
+}
+
+impl Plan {
+    /// Lays out `routine`, with its deadline already set, starting at `start`, leaving each
+    /// task's buffer between it and the next. Compression below `heavy` gets a warning.
+    pub fn new(name: &str, routine: &Routine, start: DateTime<Local>, heavy: f64) -> Self {
+        let mut end = start;
+        let tasks: Vec<PlannedTask> = routine
+            .tasks
+            .iter()
+            .enumerate()
+            .map(|(i, task)| {
+                let task_start = end;
+                let task_end = task_start + task.duration;
+                end = task_end + routine.buffer(i);
+                PlannedTask {
+                    name: task.name.clone(),
+                    start: task_start,
+                    end: task_end,
+                    duration: task.duration.as_secs(),
+                    original: task.original_duration.as_secs(),
+                }
+            })
+            .collect();
+        let compression = routine.compression(start).unwrap_or(1.0_f64);
+        let mut warnings = vec![];
+        if !routine.fits() || compression <= 0.0_f64 {
+            warnings.push("The deadline is impossible: there's no time for the tasks.".to_string());
+        } else if compression < heavy {
+            warnings.push(format!(
+                "Heavy compression: tasks get only {:.0}% of their planned time.",
+                compression * 100.0_f64
+            ));
+        }
+        if let Some(deadline) = routine.deadline() {
+            if deadline.date_naive() != start.date_naive() {
+                warnings.push(format!(
+                    "The deadline is tomorrow, {}.",
+                    deadline.format("%a %H:%M")
+                ));
+            }
+        }
+        Self {
+            routine: name.to_string(),
+            start,
+            end,
+            deadline: routine.deadline(),
+            compression,
+            tasks,
+            warnings,
+        }
+    }
+
+    /// A table of the tasks, then the totals and any warnings.
+    pub fn to_text(&self, clock_format: &str) -> String {
+        let clock = |time: DateTime<Local>| time.format(clock_format).to_string();
+        let name_width = self
+            .tasks
+            .iter()
+            .map(|task| task.name.chars().count())
+            .max()
+            .unwrap_or(0);
+        let mut text = String::new();
+        for task in &self.tasks {
+            let _ = writeln!(
+                text,
+                "{} - {}  {:<name_width$}  {}",
+                clock(task.start),
+                clock(task.end),
+                task.name,
+                format_duration(Duration::from_secs(task.duration))
+            );
+        }
+        let _ = write!(
+            text,
+            "\n{} in total, ending at {}",
+            format_duration((self.end - self.start).to_std().unwrap_or_default()),
+            clock(self.end)
+        );
+        if let Some(deadline) = self.deadline {
+            let _ = write!(text, ", deadline {}", clock(deadline));
+        }
+        if self.compression < 1.0_f64 {
+            let _ = write!(
+                text,
+                ", tasks shrunk to {:.0}%",
+                self.compression * 100.0_f64
+            );
+        }
+        text.push('\n');
+        for warning in &self.warnings {
+            let _ = writeln!(text, "Warning: {warning}");
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::routine::Task;
+
+    fn routine(deadline_in: u64) -> (Routine, DateTime<Local>) {
+        let mut routine = Routine::with_tasks(vec![Task::new("a", 60), Task::new("b", 180)]);
+        let start = Local::now();
+        routine.set_deadline_at(start + Duration::from_secs(deadline_in), start);
+        (routine, start)
+    }
+
+    #[test]
+    fn shrinks_to_deadline() {
+        let (routine, start) = routine(120);
+
+        let plan = Plan::new("test", &routine, start, 0.4);
+
+        assert_eq!(plan.tasks[1].duration, 90);
+        assert_eq!(plan.tasks[1].start, start + Duration::from_secs(30));
+        assert!(plan.warnings.is_empty());
+    }
+
+    #[test]
+    fn leaves_buffers_between_tasks() {
+        let mut routine = Routine::with_tasks(vec![Task::new("a", 60), Task::new("b", 180)]);
+        routine.set_buffer(Duration::from_secs(30));
+        let start = Local::now();
+
+        let plan = Plan::new("test", &routine, start, 0.4);
+
+        assert_eq!(plan.tasks[0].end, start + Duration::from_secs(60));
+        assert_eq!(plan.tasks[1].start, start + Duration::from_secs(90));
+        assert_eq!(plan.end, start + Duration::from_secs(270));
+    }
+
+    #[test]
+    fn warns_about_heavy_compression() {
+        let (routine, start) = routine(60);
+
+        let plan = Plan::new("test", &routine, start, 0.5);
+
+        assert!(plan.warnings[0].starts_with("Heavy compression: tasks get only 25%"));
+    }
+}
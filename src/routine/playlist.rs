@@ -0,0 +1,148 @@
+// Several routines run back to back as one session, given on the command line or listed in a
+// playlist file: a file ending in `.playlist` with the path of a routine on each line.
+// Relative paths are from the playlist's directory, and lines starting with `#` are comments.
+
+use color_eyre::eyre::{eyre, Result};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// The extension that marks a playlist, rather than a routine.
+const EXTENSION: &str = "playlist";
+
+/// The routines in `paths`, in order, with playlists replaced by what they list.
+///
+/// # Errors
+///
+/// Will return an error if a playlist can't be read, or lists another playlist.
+pub fn expand(paths: &[String]) -> Result<Vec<PathBuf>> {
+    let mut routines = vec![];
+    for path in paths.iter().map(Path::new) {
+        if is_playlist(path) {
+            routines.extend(read_playlist(path)?);
+        } else {
+            routines.push(path.to_path_buf());
+        }
+    }
+    Ok(routines)
+}
+
+/// Whether `path` is a playlist, going by its extension.
+pub fn is_playlist(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == EXTENSION)
+}
+
+/// The paths a playlist lists, as written, with their line numbers.
+pub fn entries(text: &str) -> impl Iterator<Item = (u64, &str)> {
+    (1_u64..)
+        .zip(text.lines().map(str::trim))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+}
+
+fn read_playlist(path: &Path) -> Result<Vec<PathBuf>> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| eyre!("Can't read playlist {}: {e}", path.display()))?;
+    let dir = path.parent().unwrap_or(Path::new(""));
+    entries(&text)
+        .map(|(_, line)| {
+            let routine = dir.join(line);
+            if is_playlist(&routine) {
+                Err(eyre!(
+                    "Playlist {} lists another playlist, {line}",
+                    path.display()
+                ))
+            } else {
+                Ok(routine)
+            }
+        })
+        .collect()
+}
+
+/// What each routine is called in the routine table and the logs: its file name, with as
+/// many of its directories in front as it takes to tell it apart from the others.
+pub fn routine_names(paths: &[PathBuf]) -> Vec<String> {
+    let parts: Vec<Vec<Component>> = paths
+        .iter()
+        .map(|path| path.components().rev().collect())
+        .collect();
+    let mut depths = vec![1; paths.len()];
+    loop {
+        let names: Vec<String> = parts
+            .iter()
+            .zip(&depths)
+            .map(|(parts, &depth)| {
+                let name: PathBuf = parts.iter().take(depth).rev().collect();
+                name.to_string_lossy().into_owned()
+            })
+            .collect();
+        let mut longer = false;
+        for ((name, parts), depth) in names.iter().zip(&parts).zip(&mut depths) {
+            let clashes = names.iter().filter(|other| *other == name).count() > 1;
+            // the same file twice keeps the same name
+            if clashes && *depth < parts.len() {
+                *depth += 1;
+                longer = true;
+            }
+        }
+        if !longer {
+            return names;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn playlists_are_expanded() {
+        let dir = std::env::temp_dir().join(format!("accordion-playlist-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let playlist = dir.join("evening.playlist");
+        fs::write(&playlist, "# after dinner\ncleanup.csv\n\nbedtime.csv\n").unwrap();
+
+        let paths = vec![
+            playlist.to_string_lossy().into_owned(),
+            "wind_down.csv".to_string(),
+        ];
+        let routines = expand(&paths).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            routines,
+            [
+                dir.join("cleanup.csv"),
+                dir.join("bedtime.csv"),
+                PathBuf::from("wind_down.csv")
+            ]
+        );
+    }
+
+    #[test]
+    fn names_tell_routines_apart() {
+        let paths: Vec<PathBuf> = [
+            "kids/shoes.csv",
+            "me/shoes.csv",
+            "bedtime.csv",
+            "a/x/teeth.csv",
+            "b/x/teeth.csv",
+        ]
+        .iter()
+        .map(PathBuf::from)
+        .collect();
+
+        assert_eq!(
+            routine_names(&paths),
+            [
+                "kids/shoes.csv",
+                "me/shoes.csv",
+                "bedtime.csv",
+                "a/x/teeth.csv",
+                "b/x/teeth.csv"
+            ]
+        );
+        assert_eq!(
+            routine_names(&[PathBuf::from("a.csv"), PathBuf::from("a.csv")]),
+            ["a.csv", "a.csv"]
+        );
+    }
+}
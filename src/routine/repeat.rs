@@ -0,0 +1,288 @@
+// Blocks of tasks that repeat, written in a routine file between a `repeat` row and an
+// `end repeat` row, both without a duration:
+//
+// repeat 3,
+// pushups,1m
+// rest,30s
+// end repeat,
+//
+// `repeat 3` numbers the copies, like `pushups (1/3)`, and `repeat Ana; Ben` names them,
+// like `shoes (Ana)`. Blocks can be nested.
+//
+// Each copy remembers the block it came from, so saving writes the block back once instead of
+// every copy of it.
+
+use super::Task;
+
+/// The most times a block can be repeated by count.
+const MAX_REPEATS: usize = 99;
+
+/// The name of the row that ends a block.
+pub const END: &str = "end repeat";
+
+#[derive(Debug, PartialEq)]
+pub enum Directive {
+    /// Starts a block, with what to call each copy of it.
+    Repeat(Vec<String>),
+    End,
+}
+
+/// The directive in a row, if it is one: a task name like `repeat 3` or `end repeat`, with no
+/// duration.
+///
+/// # Errors
+///
+/// Will return an error if it is a `repeat` row, but what to repeat by can't be read.
+pub fn directive(name: &str, duration: &str) -> Option<Result<Directive, String>> {
+    if !duration.is_empty() {
+        return None;
+    }
+    if name.eq_ignore_ascii_case(END) {
+        return Some(Ok(Directive::End));
+    }
+    let by = name
+        .get(..7)
+        .filter(|start| start.eq_ignore_ascii_case("repeat "))
+        .and_then(|_| name.get(7..))?
+        .trim();
+    if by.contains(';') {
+        let labels: Vec<String> = by
+            .split(';')
+            .map(str::trim)
+            .filter(|label| !label.is_empty())
+            .map(str::to_string)
+            .collect();
+        return Some(Ok(Directive::Repeat(labels)));
+    }
+    Some(match by.parse::<usize>() {
+        Ok(n @ 1..=MAX_REPEATS) => Ok(Directive::Repeat(
+            (1..=n).map(|i| format!("{i}/{n}")).collect(),
+        )),
+        _ => Err(format!(
+            "repeat a block 1 to {MAX_REPEATS} times, like \"repeat 3\", or once for each name, like \"repeat Ana; Ben\""
+        )),
+    })
+}
+
+/// Which copy of a repeated block a task is in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RepeatCopy {
+    /// The block, counting from zero in the order they start in the routine file.
+    pub block: usize,
+    /// What each copy of the block is called.
+    pub labels: Vec<String>,
+    /// Which of the labels is this copy's.
+    pub label: usize,
+}
+
+/// Builds the routine's tasks a row at a time, copying each repeated block when it ends.
+#[derive(Debug, Default)]
+pub struct Expander {
+    tasks: Vec<Task>,
+    /// Blocks that haven't ended yet, innermost last, with the line each started on and its
+    /// number.
+    open: Vec<(u64, usize, Vec<String>, Vec<Task>)>,
+    /// How many blocks have started so far.
+    blocks: usize,
+}
+
+impl Expander {
+    pub fn task(&mut self, task: Task) {
+        match self.open.last_mut() {
+            Some((.., block)) => block.push(task),
+            None => self.tasks.push(task),
+        }
+    }
+
+    pub fn directive(&mut self, directive: Directive, line: u64) -> Result<(), String> {
+        match directive {
+            Directive::Repeat(labels) => {
+                self.open.push((line, self.blocks, labels, vec![]));
+                self.blocks += 1;
+            }
+            Directive::End => {
+                let (_, number, labels, block) = self
+                    .open
+                    .pop()
+                    .ok_or("\"end repeat\" without a \"repeat\" before it")?;
+                for (i, label) in labels.iter().enumerate() {
+                    for task in &block {
+                        let mut copy = task.clone();
+                        copy.name = format!("{} ({label})", task.name);
+                        // blocks around this one end later, so they go in front
+                        copy.repeat.insert(
+                            0,
+                            RepeatCopy {
+                                block: number,
+                                labels: labels.clone(),
+                                label: i,
+                            },
+                        );
+                        self.task(copy);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The tasks, with every block copied out.
+    ///
+    /// # Errors
+    ///
+    /// Will return the line of the first block that never ended, if there is one.
+    pub fn finish(self) -> Result<Vec<Task>, (u64, String)> {
+        match self.open.first() {
+            Some((line, ..)) => Err((*line, "\"repeat\" without an \"end repeat\"".to_string())),
+            None => Ok(self.tasks),
+        }
+    }
+}
+
+/// A row to write back to a routine file.
+#[derive(Debug)]
+pub enum Row<'a> {
+    /// A task, with its name as it was before its blocks were copied out.
+    Task(&'a Task, &'a str),
+    /// The start of a block, with what to call each copy of it.
+    Repeat(String),
+    End,
+}
+
+/// The rows to write for `tasks`: each repeated block once, where its first task is, made from
+/// the tasks of the copy that task is in. Tasks in its other copies are left out, since the
+/// block makes them again when the file is read.
+pub fn collapse(tasks: &[Task]) -> Vec<Row<'_>> {
+    collapse_at(&tasks.iter().collect::<Vec<_>>(), 0)
+}
+
+fn collapse_at<'a>(tasks: &[&'a Task], depth: usize) -> Vec<Row<'a>> {
+    let mut rows = vec![];
+    let mut written = vec![];
+    for &task in tasks {
+        let Some(copy) = task.repeat.get(depth) else {
+            rows.push(Row::Task(task, original_name(task)));
+            continue;
+        };
+        if written.contains(&copy.block) {
+            continue;
+        }
+        written.push(copy.block);
+        let first: Vec<&Task> = tasks
+            .iter()
+            .copied()
+            .filter(|other| other.repeat.get(depth) == Some(copy))
+            .collect();
+        rows.push(Row::Repeat(repeat_name(&copy.labels)));
+        rows.extend(collapse_at(&first, depth + 1));
+        rows.push(Row::End);
+    }
+    rows
+}
+
+/// The name of a row starting a block with `labels`, as [`directive`] reads it.
+fn repeat_name(labels: &[String]) -> String {
+    let n = labels.len();
+    let numbered = labels
+        .iter()
+        .enumerate()
+        .all(|(i, label)| *label == format!("{}/{n}", i + 1));
+    match labels {
+        _ if numbered => format!("repeat {n}"),
+        [label] => format!("repeat {label};"),
+        _ => format!("repeat {}", labels.join("; ")),
+    }
+}
+
+/// The task's name without the labels its blocks added, unless it has been renamed since.
+fn original_name(task: &Task) -> &str {
+    let mut name = task.name.as_str();
+    for copy in &task.repeat {
+        let suffix = copy
+            .labels
+            .get(copy.label)
+            .map(|label| format!(" ({label})"))
+            .unwrap_or_default();
+        match name.strip_suffix(&suffix) {
+            Some(stripped) if !suffix.is_empty() => name = stripped,
+            _ => break,
+        }
+    }
+    name
+}
+
+/// The blocks a task put between `before` and `after` is in: those they're both in.
+pub fn shared(before: &[RepeatCopy], after: &[RepeatCopy]) -> Vec<RepeatCopy> {
+    before
+        .iter()
+        .zip(after)
+        .take_while(|(before, after)| before == after)
+        .map(|(copy, _)| copy.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(tasks: &[Task]) -> Vec<&str> {
+        tasks.iter().map(|task| task.name.as_str()).collect()
+    }
+
+    #[test]
+    fn reads_directives() {
+        assert_eq!(directive("pushups", "1m"), None);
+        assert_eq!(directive("repeat 3", "1m"), None);
+        assert_eq!(directive("End Repeat", ""), Some(Ok(Directive::End)));
+        assert_eq!(
+            directive("repeat 2", ""),
+            Some(Ok(Directive::Repeat(vec!["1/2".into(), "2/2".into()])))
+        );
+        assert_eq!(
+            directive("Repeat Ana; Ben;", ""),
+            Some(Ok(Directive::Repeat(vec!["Ana".into(), "Ben".into()])))
+        );
+        assert!(matches!(directive("repeat 0", ""), Some(Err(_))));
+        assert!(matches!(directive("repeat lots", ""), Some(Err(_))));
+    }
+
+    #[test]
+    fn nested_blocks() {
+        let mut expander = Expander::default();
+        expander.task(Task::new("warm up", 60));
+        expander
+            .directive(Directive::Repeat(vec!["Ana".into(), "Ben".into()]), 3)
+            .unwrap();
+        expander.task(Task::new("shoes", 60));
+        expander
+            .directive(directive("repeat 2", "").unwrap().unwrap(), 5)
+            .unwrap();
+        expander.task(Task::new("lace", 10));
+        expander.directive(Directive::End, 7).unwrap();
+        expander.directive(Directive::End, 8).unwrap();
+
+        assert_eq!(
+            names(&expander.finish().unwrap()),
+            [
+                "warm up",
+                "shoes (Ana)",
+                "lace (1/2) (Ana)",
+                "lace (2/2) (Ana)",
+                "shoes (Ben)",
+                "lace (1/2) (Ben)",
+                "lace (2/2) (Ben)",
+            ]
+        );
+    }
+
+    #[test]
+    fn unbalanced_blocks() {
+        let mut expander = Expander::default();
+        assert!(expander.directive(Directive::End, 2).is_err());
+        expander
+            .directive(Directive::Repeat(vec!["1/1".into()]), 4)
+            .unwrap();
+
+        assert_eq!(expander.finish().unwrap_err().0, 4);
+    }
+}
@@ -1,16 +1,35 @@
 use std::time::Duration;
 
+pub mod duration;
 pub mod parse_new;
-pub use parse_new::parse_new;
+pub use parse_new::{format_task, parse_edit, parse_new, NewTask, Position};
 
-#[derive(Debug, Copy, Clone)]
+use super::repeat::RepeatCopy;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum CompletionStatus {
     NotYet,
     Done,
     Skipped,
 }
 
-#[derive(Debug)]
+/// Something to remember to do during a task, ticked off without becoming a task itself.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChecklistItem {
+    pub text: String,
+    pub done: bool,
+}
+
+impl ChecklistItem {
+    pub fn new(text: &str) -> Self {
+        Self {
+            text: text.to_owned(),
+            done: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct Task {
     /// How much time has already been spent on the task?
     pub elapsed: Duration,
@@ -22,6 +41,29 @@ pub struct Task {
     pub name: String,
     /// Current duration that may be shrunk
     pub duration: Duration,
+    /// Is the task kept at its original duration, never shrunk to fit?
+    pub rigid: bool,
+    /// Does the task give up its time before any other task shrinks?
+    pub optional: bool,
+    /// The least the task can be shrunk to.
+    pub min_duration: Duration,
+    pub tags: Vec<String>,
+    pub note: Option<String>,
+    pub checklist: Vec<ChecklistItem>,
+    /// Time set aside for switching to the next task, if it isn't the routine's default.
+    pub buffer: Option<Duration>,
+    /// How much of the buffer after the task has gone to time spent without a task.
+    pub buffer_used: Duration,
+    /// Is the task a break, put in after a stretch of other tasks? Breaks don't count
+    /// toward progress.
+    pub is_break: bool,
+    /// Which of the routines in the session the task belongs to, counting from zero.
+    pub source: usize,
+    /// Which copy of each repeated block in the routine file the task is in, outermost first.
+    pub repeat: Vec<RepeatCopy>,
+    /// Is the task only for this session, like another go at a task, and left out when the
+    /// routine is saved?
+    pub session_only: bool,
 }
 
 impl Task {
@@ -32,13 +74,46 @@ impl Task {
             original_duration: Duration::new(duration, 0),
             duration: Duration::new(duration, 0),
             status: CompletionStatus::NotYet,
+            rigid: false,
+            optional: false,
+            min_duration: Duration::ZERO,
+            tags: vec![],
+            note: None,
+            checklist: vec![],
+            buffer: None,
+            buffer_used: Duration::ZERO,
+            is_break: false,
+            source: 0,
+            repeat: vec![],
+            session_only: false,
+        }
+    }
+
+    /// The task as it would be before starting it, with its original duration and nothing
+    /// on its checklist ticked.
+    pub fn fresh_copy(&self) -> Self {
+        let mut copy = self.clone();
+        copy.elapsed = Duration::ZERO;
+        copy.duration = copy.original_duration;
+        copy.status = CompletionStatus::NotYet;
+        copy.buffer_used = Duration::ZERO;
+        for item in &mut copy.checklist {
+            item.done = false;
         }
+        copy
     }
 
     pub fn remaining(&self) -> Duration {
         self.duration.saturating_sub(self.elapsed)
     }
 
+    /// Ticks the checklist item at `item`, or unticks it if it was ticked.
+    pub fn toggle_checklist_item(&mut self, item: usize) -> Option<&ChecklistItem> {
+        let item = self.checklist.get_mut(item)?;
+        item.done = !item.done;
+        Some(item)
+    }
+
     pub fn elapse(&mut self, duration: Duration) {
         self.elapsed += duration;
     }
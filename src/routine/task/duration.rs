@@ -0,0 +1,350 @@
+// Reading and writing task durations.
+//
+// Durations can be written with units, like `1h30m`, `90s`, `1.5m` or `1h 30m`, or like a
+// clock, as `1:30` (minutes and seconds) or `01:02:03` (hours, minutes and seconds). A bare
+// number, like `45`, is in the default unit. Anything that could mean two things, like
+// `1m30` or `1:5`, is rejected rather than guessed at.
+
+use serde::Deserialize;
+use std::fmt;
+
+/// The unit for a duration written as a bare number.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DurationUnit {
+    #[serde(alias = "h")]
+    Hours,
+    #[default]
+    #[serde(alias = "m")]
+    Minutes,
+    #[serde(alias = "s")]
+    Seconds,
+}
+
+impl DurationUnit {
+    fn from_char(unit: char) -> Option<Self> {
+        match unit {
+            'h' => Some(Self::Hours),
+            'm' => Some(Self::Minutes),
+            's' => Some(Self::Seconds),
+            _ => None,
+        }
+    }
+
+    fn seconds(self) -> u64 {
+        match self {
+            Self::Hours => 60 * 60,
+            Self::Minutes => 60,
+            Self::Seconds => 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DurationError {
+    Empty,
+    Negative,
+    InvalidNumber(String),
+    UnknownUnit(String),
+    RepeatedUnit(char),
+    /// Like `30s1m`.
+    UnitsOutOfOrder,
+    /// A number without a unit after ones with units, like the 30 in `1m30`.
+    MissingUnit(String),
+    /// Units and colons together, like `1:30m`.
+    MixedFormats,
+    /// More than three parts, or an empty one, like `1::30`.
+    WrongClockParts,
+    /// Minutes or seconds of 60 or more, like `1:75`.
+    ClockPartTooLarge(String),
+    /// Minutes or seconds without exactly two digits, like the 5 in `1:5`.
+    AmbiguousClockPart(String),
+    /// Durations are counted in whole seconds.
+    NotWholeSeconds,
+    TooLong,
+}
+
+impl fmt::Display for DurationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "no duration"),
+            Self::Negative => write!(f, "durations can't be negative"),
+            Self::InvalidNumber(n) => write!(f, "\"{n}\" isn't a number"),
+            Self::UnknownUnit(u) => write!(f, "unknown unit \"{u}\"; use h, m or s"),
+            Self::RepeatedUnit(u) => write!(f, "\"{u}\" is there more than once"),
+            Self::UnitsOutOfOrder => write!(f, "units should go from hours to seconds"),
+            Self::MissingUnit(n) => write!(f, "\"{n}\" needs a unit"),
+            Self::MixedFormats => write!(f, "use either units or colons, not both"),
+            Self::WrongClockParts => write!(f, "write it like 1:30 or 1:02:03"),
+            Self::ClockPartTooLarge(n) => write!(f, "\"{n}\" is more than 59"),
+            Self::AmbiguousClockPart(n) => {
+                write!(f, "\"{n}\" after a colon should have two digits")
+            }
+            Self::NotWholeSeconds => write!(f, "durations are in whole seconds"),
+            Self::TooLong => write!(f, "too long"),
+        }
+    }
+}
+
+impl std::error::Error for DurationError {}
+
+/// Returns the number of seconds. A bare number is in `default_unit`.
+///
+/// # Errors
+///
+/// Will return an error if `raw` isn't a duration, or if it could mean more than one.
+pub fn parse_duration(raw: &str, default_unit: DurationUnit) -> Result<u64, DurationError> {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return Err(DurationError::Empty);
+    }
+    if raw.starts_with('-') {
+        return Err(DurationError::Negative);
+    }
+    if raw.contains(':') {
+        if raw.chars().any(char::is_alphabetic) {
+            return Err(DurationError::MixedFormats);
+        }
+        return parse_clock(raw);
+    }
+    parse_units(raw, default_unit)
+}
+
+/// `m:ss` or `h:mm:ss`.
+fn parse_clock(raw: &str) -> Result<u64, DurationError> {
+    let parts: Vec<&str> = raw.split(':').map(str::trim).collect();
+    if !(2..=3).contains(&parts.len()) || parts.iter().any(|part| part.is_empty()) {
+        return Err(DurationError::WrongClockParts);
+    }
+    let mut total = 0_u64;
+    for (i, part) in parts.iter().enumerate() {
+        if !part.chars().all(|c| c.is_ascii_digit()) {
+            return Err(DurationError::InvalidNumber((*part).to_string()));
+        }
+        let value: u64 = part.parse().map_err(|_| DurationError::TooLong)?;
+        if i > 0 {
+            if part.len() != 2 {
+                return Err(DurationError::AmbiguousClockPart((*part).to_string()));
+            }
+            if value >= 60 {
+                return Err(DurationError::ClockPartTooLarge((*part).to_string()));
+            }
+        }
+        total = total
+            .checked_mul(60)
+            .and_then(|total| total.checked_add(value))
+            .ok_or(DurationError::TooLong)?;
+    }
+    Ok(total)
+}
+
+/// Numbers each followed by a unit, largest first, or one bare number.
+fn parse_units(raw: &str, default_unit: DurationUnit) -> Result<u64, DurationError> {
+    let mut chars = raw.chars().peekable();
+    let mut total = 0_u64;
+    let mut last_unit: Option<DurationUnit> = None;
+    let mut parts = 0_usize;
+    while chars.peek().is_some() {
+        let number: String =
+            std::iter::from_fn(|| chars.next_if(|c| c.is_ascii_digit() || *c == '.' || *c == ','))
+                .collect();
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        let unit: String = std::iter::from_fn(|| chars.next_if(|c| c.is_alphabetic())).collect();
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if number.is_empty() {
+            return Err(match (unit.is_empty(), chars.next()) {
+                (false, _) => DurationError::InvalidNumber(unit),
+                (true, Some(c)) => DurationError::InvalidNumber(c.to_string()),
+                (true, None) => DurationError::Empty,
+            });
+        }
+        parts += 1;
+        let unit = match unit.as_str() {
+            "" if parts == 1 && chars.peek().is_none() => default_unit,
+            "" => return Err(DurationError::MissingUnit(number)),
+            _ => {
+                let mut letters = unit.chars();
+                match (
+                    letters.next().and_then(DurationUnit::from_char),
+                    letters.next(),
+                ) {
+                    (Some(unit), None) => unit,
+                    _ => return Err(DurationError::UnknownUnit(unit)),
+                }
+            }
+        };
+        match last_unit {
+            Some(last) if last == unit => {
+                return Err(DurationError::RepeatedUnit(unit_char(unit)));
+            }
+            Some(last) if last.seconds() < unit.seconds() => {
+                return Err(DurationError::UnitsOutOfOrder);
+            }
+            _ => (),
+        }
+        last_unit = Some(unit);
+        total = total
+            .checked_add(seconds_of(&number, unit)?)
+            .ok_or(DurationError::TooLong)?;
+    }
+    Ok(total)
+}
+
+fn unit_char(unit: DurationUnit) -> char {
+    match unit {
+        DurationUnit::Hours => 'h',
+        DurationUnit::Minutes => 'm',
+        DurationUnit::Seconds => 's',
+    }
+}
+
+/// Exactly how many seconds `number` of `unit` is, without floating point rounding.
+fn seconds_of(number: &str, unit: DurationUnit) -> Result<u64, DurationError> {
+    let invalid = || DurationError::InvalidNumber(number.to_string());
+    let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+    if whole.is_empty() && fraction.is_empty() || number.contains(',') || fraction.contains('.') {
+        return Err(invalid());
+    }
+    let digits = format!("{whole}{fraction}");
+    let mantissa: u64 = digits.parse().map_err(|_| DurationError::TooLong)?;
+    let scale = u32::try_from(fraction.len())
+        .ok()
+        .and_then(|places| 10_u64.checked_pow(places))
+        .ok_or(DurationError::TooLong)?;
+    let scaled = mantissa
+        .checked_mul(unit.seconds())
+        .ok_or(DurationError::TooLong)?;
+    if scaled % scale != 0 {
+        return Err(DurationError::NotWholeSeconds);
+    }
+    Ok(scaled / scale)
+}
+
+/// Formats a number of seconds like `1h5m30s`, which [`parse_duration`] reads back. For
+/// showing a duration in the interface, with spaces, see [`crate::ui::format_duration`].
+pub fn format_duration_compact(secs: u64) -> String {
+    let (h, m, s) = (secs / 3600, secs / 60 % 60, secs % 60);
+    let mut out = String::new();
+    if h > 0 {
+        out += &format!("{h}h");
+    }
+    if m > 0 {
+        out += &format!("{m}m");
+    }
+    if s > 0 || out.is_empty() {
+        out += &format!("{s}s");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(raw: &str) -> Result<u64, DurationError> {
+        parse_duration(raw, DurationUnit::Minutes)
+    }
+
+    #[test]
+    fn units() {
+        assert_eq!(parse("1h30m"), Ok(90 * 60));
+        assert_eq!(parse("90s"), Ok(90));
+        assert_eq!(parse("1h 2m 3s"), Ok(3723));
+        assert_eq!(parse("2 m"), Ok(120));
+        assert_eq!(parse("1h3s"), Ok(3603));
+    }
+
+    #[test]
+    fn decimals() {
+        assert_eq!(parse("1.5m"), Ok(90));
+        assert_eq!(parse("1.3m"), Ok(78));
+        assert_eq!(parse("0.25h"), Ok(15 * 60));
+        assert_eq!(parse(".5m"), Ok(30));
+        assert_eq!(parse("1.5s"), Err(DurationError::NotWholeSeconds));
+        assert_eq!(
+            parse("1.2.3m"),
+            Err(DurationError::InvalidNumber("1.2.3".to_string()))
+        );
+        assert_eq!(
+            parse("1,5m"),
+            Err(DurationError::InvalidNumber("1,5".to_string()))
+        );
+    }
+
+    #[test]
+    fn clock() {
+        assert_eq!(parse("1:30"), Ok(90));
+        assert_eq!(parse("01:02:03"), Ok(3723));
+        assert_eq!(parse("0:00"), Ok(0));
+        assert_eq!(parse("90:00"), Ok(90 * 60));
+    }
+
+    #[test]
+    fn bare_number_uses_default_unit() {
+        assert_eq!(parse("45"), Ok(45 * 60));
+        assert_eq!(parse_duration("45", DurationUnit::Seconds), Ok(45));
+        assert_eq!(parse_duration("1.5", DurationUnit::Hours), Ok(90 * 60));
+    }
+
+    #[test]
+    fn rejects_ambiguous() {
+        assert_eq!(
+            parse("1m30"),
+            Err(DurationError::MissingUnit("30".to_string()))
+        );
+        assert_eq!(
+            parse("1 30"),
+            Err(DurationError::MissingUnit("1".to_string()))
+        );
+        assert_eq!(
+            parse("1:5"),
+            Err(DurationError::AmbiguousClockPart("5".to_string()))
+        );
+        assert_eq!(parse("1:30m"), Err(DurationError::MixedFormats));
+    }
+
+    #[test]
+    fn rejects_malformed() {
+        assert_eq!(parse(""), Err(DurationError::Empty));
+        assert_eq!(parse("  "), Err(DurationError::Empty));
+        assert_eq!(parse("-5m"), Err(DurationError::Negative));
+        assert_eq!(
+            parse("away"),
+            Err(DurationError::InvalidNumber("away".to_string()))
+        );
+        assert_eq!(
+            parse("5 minutes"),
+            Err(DurationError::UnknownUnit("minutes".to_string()))
+        );
+        assert_eq!(
+            parse("5x"),
+            Err(DurationError::UnknownUnit("x".to_string()))
+        );
+        assert_eq!(parse("1m2m"), Err(DurationError::RepeatedUnit('m')));
+        assert_eq!(parse("30s1m"), Err(DurationError::UnitsOutOfOrder));
+        assert_eq!(
+            parse("1:75"),
+            Err(DurationError::ClockPartTooLarge("75".to_string()))
+        );
+        assert_eq!(parse("1::30"), Err(DurationError::WrongClockParts));
+        assert_eq!(parse("1:00:00:00"), Err(DurationError::WrongClockParts));
+        assert_eq!(parse("1:3O"), Err(DurationError::MixedFormats));
+        assert_eq!(
+            parse("5m!"),
+            Err(DurationError::InvalidNumber("!".to_string()))
+        );
+    }
+
+    #[test]
+    fn rejects_overflow() {
+        assert_eq!(parse("99999999999999999999s"), Err(DurationError::TooLong));
+        assert_eq!(parse("9999999999999999h"), Err(DurationError::TooLong));
+    }
+
+    #[test]
+    fn format_round_trip() {
+        for secs in [0_u64, 59, 60, 61, 3600, 3723, 90_000] {
+            assert_eq!(parse(&format_duration_compact(secs)), Ok(secs));
+        }
+    }
+}
@@ -7,92 +7,218 @@
 //
 // ambiguity: should tasks created with a specified time have that as their original
 // time, or should they compress as necessary for the current time budget?
-use std::num::ParseIntError;
-use std::str::FromStr;
-
+use super::duration::{format_duration_compact, parse_duration, DurationError, DurationUnit};
 use crate::routine::Task;
 
+use std::time::Duration;
+
 const DEFAULT_DURATION_SECS: u64 = 5 * 60;
 
 // TODO consider refactoring to use Winnow? Keep an eye out for if/when it isn't overkill
 
-pub fn parse_new(raw: &str) -> Task {
-    // TODO figure out error type. I want it to fail silently most of the time if
-    // duration parsing fails, but if the task is nameless and durationless, assume it
-    // was a mistake and don't create the new empty task.
-    // - find the final delimiter (whitespace) not counting trailing whitespace
-    // - split on that character
-    // - attempt to parse the latter chunk as a duration
-    // -- if it works, set the name as the former chunk and the duration as the
-    // parsing result
-    // -- if it fails, assume the full unsplit chunk is the name and set the duration to
-    // the default.
-    // - create and return the task
-    let default_duration = DEFAULT_DURATION_SECS;
-    let (name, duration) = match raw.rsplit_once(' ') {
-        None => (raw.to_owned(), default_duration),
-        Some((name, possible_duration)) => {
-            let name = name.to_owned();
-            match parse_duration(possible_duration) {
-                Ok(secs) => (name, secs),
-                Err(_) => (name + " " + possible_duration, default_duration),
-            }
-            // TODO remove unwrap
-            //todo!("{}", duration.as_secs());
-        }
-    };
-    Task::new(&name, duration)
+/// Where a new task should go, when it says.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Position {
+    /// Right after the task with this name.
+    After(String),
+    End,
+}
+
+/// A task typed in the quick-add syntax, and everything in it that couldn't be understood.
+#[derive(Debug, Clone)]
+pub struct NewTask {
+    pub task: Task,
+    pub position: Option<Position>,
+    pub problems: Vec<String>,
 }
 
-/// Returns the number of seconds.
-pub fn parse_duration(raw: &str) -> Result<u64, ParseIntError> {
-    // TODO this code is so old and weird. I surely know how to do it more idiomatically now.
-    let mut number_accum = String::new();
-    let mut hours = 0;
-    let mut minutes = 0;
-    let mut seconds = 0;
-    let mut seen_hms = false;
-    for g in raw.chars() {
-        match g {
-            'h' => {
-                hours = u64::from_str(&number_accum)?;
-                number_accum = String::new();
-                seen_hms = true;
+impl NewTask {
+    /// Whether anything besides a name and a duration was given.
+    pub fn has_extras(&self) -> bool {
+        let task = &self.task;
+        self.position.is_some()
+            || task.rigid
+            || task.optional
+            || !task.min_duration.is_zero()
+            || task.buffer.is_some()
+            || !task.tags.is_empty()
+            || task.note.is_some()
+    }
+}
+
+/// Reads a task like
+/// `water plants 3m !rigid ?optional #home min:1m buffer:30s @after shower -- note`.
+/// Only the name is needed. The duration is the last word of the name, if it is one, and a
+/// bare number for it is in `default_unit`. Everything after `@after` up to the next marker
+/// is the name of the task to go after, and everything after `--` is the note.
+pub fn parse_new(raw: &str, default_unit: DurationUnit) -> NewTask {
+    let mut task = Task::new("", DEFAULT_DURATION_SECS);
+    let mut problems = vec![];
+    let mut positions = vec![];
+    let mut name: Vec<&str> = vec![];
+    let mut after: Option<Vec<&str>> = None;
+    let mut words = raw.split_whitespace();
+    while let Some(word) = words.next() {
+        let marker = word.starts_with(['!', '?', '#', '@'])
+            || word.starts_with("min:")
+            || word.starts_with("buffer:");
+        if marker {
+            if let Some(after) = after.take() {
+                positions.push(after_position(&after, &mut problems));
+            }
+        }
+        match word {
+            "--" => {
+                let note = words.by_ref().collect::<Vec<_>>().join(" ");
+                task.note = Some(note).filter(|note| !note.is_empty());
             }
-            'm' => {
-                minutes = u64::from_str(&number_accum)?;
-                number_accum = String::new();
-                seen_hms = true;
+            "!rigid" => task.rigid = true,
+            "?optional" => task.optional = true,
+            "@end" => positions.push(Some(Position::End)),
+            "@after" => after = Some(vec![]),
+            "#" => problems.push("empty tag".to_string()),
+            _ if word.starts_with('#') => task.tags.push(word.trim_start_matches('#').to_string()),
+            _ if word.starts_with('!') || word.starts_with('?') => {
+                problems.push(format!("unknown flag \"{word}\"; use !rigid or ?optional"));
             }
-            's' => {
-                seconds = u64::from_str(&number_accum)?;
-                number_accum = String::new();
-                seen_hms = true;
+            _ if word.starts_with('@') => {
+                problems.push(format!("unknown position \"{word}\"; use @after or @end"));
             }
-            ' ' => (),
-            _ => number_accum.push(g),
+            _ if word.starts_with("min:") => {
+                match parse_duration(word.trim_start_matches("min:"), default_unit) {
+                    Ok(secs) => task.min_duration = Duration::from_secs(secs),
+                    Err(e) => problems.push(format!("can't read \"{word}\": {e}")),
+                }
+            }
+            _ if word.starts_with("buffer:") => {
+                match parse_duration(word.trim_start_matches("buffer:"), default_unit) {
+                    Ok(secs) => task.buffer = Some(Duration::from_secs(secs)),
+                    Err(e) => problems.push(format!("can't read \"{word}\": {e}")),
+                }
+            }
+            _ => match after.as_mut() {
+                Some(after) => after.push(word),
+                None => name.push(word),
+            },
         }
     }
-    if seen_hms {
-        Ok(hours * 60 * 60 + minutes * 60 + seconds)
+    if let Some(after) = after {
+        positions.push(after_position(&after, &mut problems));
+    }
+    if positions.len() > 1 {
+        problems.push("more than one position".to_string());
+    }
+    task.original_duration =
+        Duration::from_secs(take_duration(&mut name, default_unit, &mut problems));
+    task.duration = task.original_duration;
+    task.name = name.join(" ");
+    if task.name.is_empty() {
+        problems.push("no task name".to_string());
+    }
+    if task.min_duration > task.original_duration {
+        problems.push("min is longer than the task".to_string());
+    }
+    NewTask {
+        task,
+        position: positions.into_iter().flatten().next(),
+        problems,
+    }
+}
+
+/// Reads a task edited from what [`format_task`] wrote: the name, then the duration. Only those
+/// can be edited, so words like `#home` or `--` are part of the name.
+pub fn parse_edit(raw: &str, default_unit: DurationUnit) -> NewTask {
+    let mut problems = vec![];
+    let mut name: Vec<&str> = raw.split_whitespace().collect();
+    let duration = take_duration(&mut name, default_unit, &mut problems);
+    let task = Task::new(&name.join(" "), duration);
+    if task.name.is_empty() {
+        problems.push("no task name".to_string());
+    }
+    NewTask {
+        task,
+        position: None,
+        problems,
+    }
+}
+
+fn after_position(name: &[&str], problems: &mut Vec<String>) -> Option<Position> {
+    if name.is_empty() {
+        problems.push("@after needs the name of a task".to_string());
+        None
     } else {
-        // TODO this is an ugly, ugly hack because I don't want to come up with
-        // a new result type right now.
-        Ok(u64::from_str("")?)
+        Some(Position::After(name.join(" ")))
+    }
+}
+
+/// Takes the duration off the end of the name, in seconds, or gives the default. A lone word
+/// is the name, unless it has a unit.
+fn take_duration(name: &mut Vec<&str>, unit: DurationUnit, problems: &mut Vec<String>) -> u64 {
+    let len = name.len();
+    // like "1h 30m"
+    if let Some([hours, minutes]) = name.get(len.saturating_sub(2)..).filter(|_| len > 2) {
+        if looks_like_duration(hours) {
+            if let Ok(secs) = parse_duration(&format!("{hours} {minutes}"), unit) {
+                name.truncate(len - 2);
+                return secs;
+            }
+        }
+    }
+    match name
+        .last()
+        .filter(|last| len > 1 || looks_like_duration(last))
+    {
+        Some(last) => match parse_duration(last, unit) {
+            Ok(secs) => {
+                name.pop();
+                secs
+            }
+            Err(e) if looks_like_duration(last) => {
+                problems.push(duration_problem(last, &e));
+                name.pop();
+                DEFAULT_DURATION_SECS
+            }
+            Err(_) => DEFAULT_DURATION_SECS,
+        },
+        None => DEFAULT_DURATION_SECS,
     }
 }
 
+fn duration_problem(word: &str, e: &DurationError) -> String {
+    format!("can't read duration \"{word}\": {e}")
+}
+
+/// A number with letters or colons in it, which was probably meant as a duration, but not an
+/// ordinal like 2nd.
+fn looks_like_duration(word: &str) -> bool {
+    let ordinal = word
+        .trim_start_matches(|c: char| c.is_ascii_digit())
+        .to_ascii_lowercase();
+    word.starts_with(|c: char| c.is_ascii_digit())
+        && word.contains(|c: char| c.is_alphabetic() || c == ':')
+        && !["st", "nd", "rd", "th"].contains(&ordinal.as_str())
+}
+
+/// Writes a task back out in the syntax that [`parse_new`] reads, so that it can be edited.
+pub fn format_task(task: &Task) -> String {
+    format!(
+        "{} {}",
+        task.name,
+        format_duration_compact(task.original_duration.as_secs())
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    use std::time::Duration;
+    fn parse(raw: &str) -> NewTask {
+        parse_new(raw, DurationUnit::Minutes)
+    }
 
     #[test]
     fn parse_task_with_duration() {
-        let input = "wash clothes 5m30s";
-
-        let task = parse_new_task(input);
+        let task = parse("wash clothes 5m30s").task;
 
         assert_eq!(task.name, "wash clothes");
         assert_eq!(task.original_duration, Duration::from_secs(5 * 60 + 30));
@@ -102,7 +228,7 @@ mod tests {
     fn no_duration() {
         let input = "shower";
 
-        let task = parse_new_task(input);
+        let task = parse(input).task;
 
         assert_eq!(task.name, "shower");
     }
@@ -111,7 +237,7 @@ mod tests {
     fn default_time() {
         let input = "shower";
 
-        let task = parse_new_task(input);
+        let task = parse(input).task;
 
         assert_eq!(task.original_duration, Duration::from_secs(5 * 60));
         // TODO: how will i decide a default?
@@ -119,24 +245,134 @@ mod tests {
 
     #[test]
     fn no_duration_with_space() {
-        let input = "wash clothes";
-
-        let task = parse_new_task(input);
+        let new = parse("wash clothes");
 
-        assert_eq!(task.name, "wash clothes");
+        assert_eq!(new.task.name, "wash clothes");
+        assert!(new.problems.is_empty());
     }
 
     #[test]
     fn no_hms_task() {
         let input = "dishes away";
 
-        let task = parse_new_task(input);
+        let task = parse(input).task;
 
         assert_eq!(task.name, "dishes away");
     }
 
     #[test]
-    fn no_hms_duration() {
-        assert!(parse_duration("away").is_err());
+    fn bare_number_in_default_unit() {
+        let task = parse_new("call 45", DurationUnit::Seconds).task;
+
+        assert_eq!(task.name, "call");
+        assert_eq!(task.original_duration, Duration::from_secs(45));
+    }
+
+    #[test]
+    fn duration_with_a_space() {
+        let task = parse("read book 1h 30m").task;
+
+        assert_eq!(task.name, "read book");
+        assert_eq!(task.original_duration, Duration::from_secs(90 * 60));
+    }
+
+    #[test]
+    fn flags_and_tags() {
+        let new = parse("water plants 3m !rigid ?optional #home #daily min:1m buffer:30s");
+
+        assert!(new.problems.is_empty());
+        assert_eq!(new.task.name, "water plants");
+        assert_eq!(new.task.original_duration, Duration::from_secs(3 * 60));
+        assert!(new.task.rigid && new.task.optional);
+        assert_eq!(new.task.tags, vec!["home", "daily"]);
+        assert_eq!(new.task.min_duration, Duration::from_secs(60));
+        assert_eq!(new.task.buffer, Some(Duration::from_secs(30)));
+        assert_eq!(new.position, None);
+    }
+
+    #[test]
+    fn positions() {
+        let new = parse("floss 2m @after brush teeth #teeth");
+
+        assert_eq!(new.task.name, "floss");
+        assert_eq!(new.task.tags, vec!["teeth"]);
+        assert_eq!(
+            new.position,
+            Some(Position::After("brush teeth".to_string()))
+        );
+        assert_eq!(parse("floss @end").position, Some(Position::End));
+    }
+
+    #[test]
+    fn note() {
+        let new = parse("stretch 10m -- hamstrings and #back");
+
+        assert_eq!(new.task.name, "stretch");
+        assert_eq!(new.task.note.as_deref(), Some("hamstrings and #back"));
+        assert!(new.task.tags.is_empty());
+    }
+
+    #[test]
+    fn reports_what_it_cant_read() {
+        let new = parse("nap 1m30 !rigd @later min:soon");
+
+        assert_eq!(new.task.name, "nap");
+        assert_eq!(
+            new.problems,
+            vec![
+                "unknown flag \"!rigd\"; use !rigid or ?optional",
+                "unknown position \"@later\"; use @after or @end",
+                "can't read \"min:soon\": \"soon\" isn't a number",
+                "can't read duration \"1m30\": \"30\" needs a unit",
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_contradictions() {
+        let problems = parse("nap !rigid 1m min:2m @end @after").problems;
+
+        assert_eq!(
+            problems,
+            vec![
+                "@after needs the name of a task",
+                "more than one position",
+                "min is longer than the task",
+            ]
+        );
+        assert_eq!(parse("#chores 5m").problems, vec!["no task name"]);
+    }
+
+    #[test]
+    fn ordinals_stay_in_the_name() {
+        let new = parse("call the 2nd");
+
+        assert_eq!(new.task.name, "call the 2nd");
+        assert!(new.problems.is_empty());
+    }
+
+    #[test]
+    fn format_round_trip() {
+        let task = Task::new("wash clothes", 60 * 60 + 30);
+
+        let task = parse(&format_task(&task)).task;
+
+        assert_eq!(task.name, "wash clothes");
+        assert_eq!(task.original_duration, Duration::from_secs(60 * 60 + 30));
+    }
+
+    #[test]
+    fn edits_keep_markers_in_the_name() {
+        let task = Task::new("#1 fan -- @home ?maybe", 90);
+
+        let new = parse_edit(&format_task(&task), DurationUnit::Minutes);
+
+        assert!(new.problems.is_empty());
+        assert_eq!(new.task.name, "#1 fan -- @home ?maybe");
+        assert_eq!(new.task.original_duration, Duration::from_secs(90));
+        assert_eq!(
+            parse_edit("  5m", DurationUnit::Minutes).problems,
+            vec!["no task name"]
+        );
     }
 }
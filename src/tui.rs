@@ -5,6 +5,7 @@ use crossterm::{
     event::{DisableMouseCapture, EnableMouseCapture},
     terminal::{self, EnterAlternateScreen, LeaveAlternateScreen},
 };
+use ratatui::layout::Rect;
 
 pub type CrosstermTerminal = ratatui::Terminal<ratatui::backend::CrosstermBackend<std::io::Stderr>>;
 
@@ -38,7 +39,9 @@ impl Tui {
         // This way, you won't have your terminal messed up if an unexpected error happens.
         let panic_hook = panic::take_hook();
         panic::set_hook(Box::new(move |panic| {
-            Self::reset().expect("failed to reset the terminal");
+            if let Err(e) = Self::reset() {
+                eprintln!("failed to reset the terminal: {e}");
+            }
             panic_hook(panic);
         }));
 
@@ -52,10 +55,19 @@ impl Tui {
     /// [`Draw`]: tui::Terminal::draw
     /// [`rendering`]: crate::ui:render
     pub fn draw(&mut self, app: &mut App) -> Result<()> {
+        let size = self.terminal.size()?;
+        app.help_height = ui::help_visible_lines(Rect::new(0, 0, size.width, size.height));
         self.terminal.draw(|frame| ui::render(app, frame))?;
         Ok(())
     }
 
+    /// Resizes the terminal interface to a new terminal size. The next draw picks the
+    /// layout that fits.
+    pub fn resize(&mut self, width: u16, height: u16) -> Result<()> {
+        self.terminal.resize(Rect::new(0, 0, width, height))?;
+        Ok(())
+    }
+
     /// Resets the terminal interface.
     ///
     /// This function is also used for the panic hook to revert
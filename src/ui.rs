@@ -3,111 +3,260 @@
 // at compile time, so this warning is noisy and not useful here. TODO can I find a way to
 // abstract in a way that expresses the safety in the code model?
 
+mod focus;
+mod help;
+mod layout;
+
+pub use help::{line_count as help_line_count, visible_lines as help_visible_lines};
+pub use layout::LayoutMode;
+
 use ratatui::{
     layout::Flex,
     prelude::{Constraint::*, *},
     style::{Color, Modifier, Style},
-    widgets::{Block, Borders, Gauge, Padding, Paragraph, Row, Table, TableState, Wrap},
+    widgets::{Block, Borders, Clear, Gauge, Padding, Paragraph, Row, Table, TableState},
 };
 use std::time::Duration;
 
-use crate::app::{list_pointer::ListPointer, App, Menu, Mode};
+use crate::app::{
+    summary::{self, Summary},
+    text_field::TextField,
+    App, Confirmation, Menu, Mode,
+};
 use crate::routine::{CompletionStatus, Task};
 
 pub fn render(app: &App, f: &mut Frame) {
-    match &app.help_menu {
-        true => render_help_menu(f),
-        false => {
-            let layout = generate_layout(app, f);
-            render_timer(app, f, layout[0]);
-            match &app.menu_focus {
-                Mode::Navigation => render_task(app, f, layout[1]),
-                Mode::Typing(menu) => render_text_field(*menu, app, f, layout[1]),
-            }
-            render_table(app, f, layout[2]);
-            if app.debug {
-                render_debug(app, f, layout[3]);
-            }
-        }
+    let layout = LayoutMode::choose(app.config.layout, f.area());
+    match (&app.menu_focus, &app.summary, layout) {
+        (Mode::Summary, Some(summary), _) => render_summary(summary, layout, f),
+        (Mode::Navigation, _, _) if app.focus => focus::render(app, f),
+        (_, _, LayoutMode::Full) => render_full(app, f),
+        (_, _, LayoutMode::Compact) => render_compact(app, f),
+        (_, _, LayoutMode::Minimal) => render_minimal(app, f),
+    }
+    if app.help_menu {
+        help::render(app, f);
+    }
+}
+
+fn render_full(app: &App, f: &mut Frame) {
+    let task_lines = match &app.menu_focus {
+        Mode::Navigation => task_panel_lines(app).len(),
+        _ => 1,
+    };
+    let task_lines = u16::try_from(task_lines).unwrap_or(u16::MAX);
+    let [timer, current, body, footer] = layout::full(f.area(), app.debug, task_lines);
+    render_timer(app, f, timer);
+    // the table first, so that suggestions under the prompt can cover it
+    render_table(app, f, body, standard_block("Routine"));
+    match &app.menu_focus {
+        Mode::Navigation | Mode::Summary => render_task(app, f, current),
+        Mode::Typing(_) | Mode::Confirm(_) => render_prompt(app, f, current, standard_block),
+    }
+    if app.debug {
+        render_debug(app, f, footer);
     }
 }
 
-fn render_text_field(menu: Menu, app: &App, f: &mut Frame, area: Rect) {
-    let label = match menu {
+fn render_compact(app: &App, f: &mut Frame) {
+    let prompting = matches!(app.menu_focus, Mode::Typing(_) | Mode::Confirm(_));
+    let [timer, field, body] = layout::compact(f.area(), prompting);
+    render_timer_line(app, f, timer);
+    render_table(app, f, body, compact_block("Routine"));
+    render_prompt(app, f, field, compact_block);
+}
+
+fn render_minimal(app: &App, f: &mut Frame) {
+    let prompting = matches!(app.menu_focus, Mode::Typing(_) | Mode::Confirm(_));
+    let problems = !app.typing_problems().is_empty();
+    let [field, summary] = layout::minimal(f.area(), prompting, problems);
+    let formatter = app.config.clock_format.get_strftime();
+    let name = app.get_current_task_name().unwrap_or("No active task.");
+    let remaining = app
+        .get_current_task_remaining()
+        .map_or_else(String::new, |d| format!("{} left", format_duration(d)));
+    let end_time = format!("ends {}", app.get_projected_end_time().format(formatter));
+    let para = Paragraph::new(vec![name.into(), remaining.into(), end_time.into()])
+        .style(Style::new().fg(Color::Yellow));
+    f.render_widget(para, summary);
+    render_prompt(app, f, field, minimal_block);
+}
+
+fn text_field_label(menu: Menu) -> &'static str {
+    match menu {
         Menu::InsertTask => "Insert New Task",
         Menu::AppendTask => "Append New Task",
+        Menu::EditTask => "Edit Task",
         Menu::Pause => "Paused",
-    };
-    let mut para = app.text_input.clone();
-    para.set_block(standard_block(label));
-    f.render_widget(&para, area);
+    }
 }
 
-fn render_help_menu(f: &mut Frame) {
-    let block = standard_block("Help");
-    let para = help_paragraph()
-        .style(Style::new().fg(Color::Yellow))
-        .block(block);
-    f.render_widget(para, f.area());
+/// Renders whatever the user is being asked for: the text field, or a yes or no question.
+fn render_prompt(app: &App, f: &mut Frame, area: Rect, block: fn(&str) -> Block<'_>) {
+    match app.menu_focus {
+        Mode::Navigation | Mode::Summary => (),
+        Mode::Typing(Menu::Pause) => {
+            let mut title = format!(
+                "Paused for {}",
+                format_duration(app.get_current_pause_duration().unwrap_or_default())
+            );
+            let over_budget = app.pause_over_budget();
+            match over_budget {
+                None => (),
+                Some(Duration::ZERO) => title += ", out of time before the deadline",
+                Some(over) => title += &format!(", {} over budget", format_duration(over)),
+            }
+            let style = match over_budget {
+                Some(_) => Style::new().fg(Color::Red),
+                None => Style::new().fg(Color::Yellow),
+            };
+            render_text_field(&app.text_input, f, area, block(&title).style(style));
+        }
+        Mode::Typing(menu) => {
+            let problems = app.typing_problems();
+            let mut block = block(text_field_label(menu));
+            if !problems.is_empty() {
+                block = block.title_bottom(
+                    Line::from(problems.join("; ")).style(Style::new().fg(Color::Red)),
+                );
+            }
+            render_text_field(&app.text_input, f, area, block);
+            render_suggestions(app, f, area);
+        }
+        Mode::Confirm(confirmation) => {
+            let question = match confirmation {
+                Confirmation::DeleteTask => format!(
+                    "Delete \"{}\"? (y/n)",
+                    app.get_current_task_name().unwrap_or_default()
+                ),
+            };
+            let para = Paragraph::new(question)
+                .style(Style::new().fg(Color::Yellow))
+                .block(block("Confirm"));
+            f.render_widget(para, area);
+        }
+    }
 }
 
-fn help_paragraph() -> Paragraph<'static> {
-    // TODO add scroll
-    let text = "Enter : Complete
-S : Skip
-
-J, K : Navigation
-J + Shift,
-K + Shift : Navigation (skip done)
-
-I : Insert New Task
-A : Append New Task
-P : Pause (and submit message for log)
-....In input mode:
-....Enter : Submit
-....Esc : Discard
-....Syntax is TASK NAME [__m__s]
-
-? : Help Menu
-D : Debug Panel
-
-Ctrl + C : Quit Accordion Task
-";
-    Paragraph::new(text).wrap(Wrap { trim: true })
+/// Draws the suggestions for the task being typed in a box just under the text field in
+/// `field`, on top of whatever is there.
+fn render_suggestions(app: &App, f: &mut Frame, field: Rect) {
+    let suggestions = app.suggestions();
+    if suggestions.is_empty() {
+        return;
+    }
+    let room = f.area().bottom().saturating_sub(field.bottom());
+    let height = u16::try_from(suggestions.len())
+        .unwrap_or(u16::MAX)
+        .saturating_add(2)
+        .min(room);
+    if height < 3 {
+        return;
+    }
+    let area = Rect::new(field.x, field.bottom(), field.width, height);
+    let lines: Vec<Line> = suggestions
+        .iter()
+        .enumerate()
+        .map(|(i, suggestion)| {
+            let typical = suggestion.typical.map_or_else(String::new, |typical| {
+                format!("~{}", format_duration(typical))
+            });
+            let style = if i == app.suggestion {
+                Style::new().add_modifier(Modifier::REVERSED)
+            } else {
+                Style::new()
+            };
+            Line::from(vec![
+                Span::raw(suggestion.name.clone()),
+                Span::styled(
+                    format!("  {typical}"),
+                    Style::new().add_modifier(Modifier::DIM),
+                ),
+            ])
+            .style(style)
+        })
+        .collect();
+    f.render_widget(Clear, area);
+    f.render_widget(
+        Paragraph::new(lines).block(compact_block("Tab to accept")),
+        area,
+    );
 }
 
-fn generate_layout(app: &App, f: &Frame) -> [Rect; 4] {
-    let width = f.area().width;
-    let header_height = 5;
-    let current_task_height = 5;
-    let footer_height = if app.debug { 15 } else { 0 };
-    let split1 = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Length(current_task_height + header_height),
-            Constraint::Min(current_task_height + header_height),
-        ])
-        .split(f.area());
-    let headers = split1[0];
-    let split3 = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Length(header_height),
-            Constraint::Min(header_height),
-        ])
-        .split(headers);
-    let header = split3[0];
-    let current = split3[1];
-    let split2 = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Max(width - header_height - footer_height),
-            Constraint::Min(footer_height),
-        ])
-        .split(split1[1]);
-    let body = split2[0];
-    let footer = split2[1];
-    [header, current, body, footer]
+/// Draws the text being typed, scrolled so the cursor is in view, with the cursor shown as
+/// a highlighted character.
+fn render_text_field(field: &TextField, f: &mut Frame, area: Rect, block: Block) {
+    let width = usize::from(block.inner(area).width).max(1);
+    let skip = (field.cursor() + 1).saturating_sub(width);
+    let chars: Vec<char> = field.text().chars().skip(skip).collect();
+    let cursor = field.cursor() - skip;
+    let before: String = chars.iter().take(cursor).collect();
+    let under = chars.get(cursor).map_or(" ".to_string(), char::to_string);
+    let after: String = chars.iter().skip(cursor + 1).collect();
+    let line = Line::from(vec![
+        Span::raw(before),
+        Span::styled(under, Style::new().add_modifier(Modifier::REVERSED)),
+        Span::raw(after),
+    ]);
+    f.render_widget(Paragraph::new(line).block(block), area);
+}
+
+fn render_summary(summary: &Summary, layout: LayoutMode, f: &mut Frame) {
+    // borders, plus padding for the standard block
+    let (block, chrome): (fn(&str) -> Block<'_>, u16) = match layout {
+        LayoutMode::Full => (standard_block, 4),
+        LayoutMode::Compact | LayoutMode::Minimal => (compact_block, 2),
+    };
+    let mut totals: Vec<Line> =
+        vec![format!("Finished in {}", format_duration(summary.elapsed)).into()];
+    totals.extend(summary.totals().into_iter().map(Line::from));
+    totals.push(
+        summary
+            .saved
+            .clone()
+            .unwrap_or_else(|| "W : Save routine   Q : Quit   Esc : Back".to_string())
+            .into(),
+    );
+    let height = u16::try_from(totals.len())
+        .unwrap_or(u16::MAX)
+        .saturating_add(chrome);
+    let [table_area, totals_area] = Layout::vertical([Min(0), Length(height)]).areas(f.area());
+
+    let rows: Vec<Row> = summary
+        .tasks
+        .iter()
+        .map(|task| {
+            Row::new(vec![
+                checkbox(task.status).to_string(),
+                task.name.clone(),
+                format_duration(task.planned),
+                format_duration(task.actual),
+                summary::format_delta(task.planned, task.actual),
+            ])
+        })
+        .collect();
+    let widths = [
+        Constraint::Length(5),
+        Constraint::Length(25),
+        Constraint::Length(12),
+        Constraint::Length(12),
+        Constraint::Length(12),
+    ];
+    let table = Table::new(rows, widths)
+        .column_spacing(1)
+        .style(Style::new().fg(Color::Yellow))
+        .header(
+            Row::new(vec!["", "Task", "Planned", "Actual", "Delta"])
+                .style(Style::new().add_modifier(Modifier::BOLD))
+                .bottom_margin(1),
+        )
+        .block(block("Routine Complete"));
+    f.render_widget(table, table_area);
+
+    let para = Paragraph::new(totals)
+        .style(Style::new().fg(Color::Yellow))
+        .block(block("Totals"));
+    f.render_widget(para, totals_area);
 }
 
 fn render_debug(app: &App, f: &mut Frame, area: Rect) {
@@ -120,6 +269,11 @@ fn render_debug(app: &App, f: &mut Frame, area: Rect) {
             app.get_projected_end_time().format(formatter)
         )
         .into(),
+        format!(
+            "total time paused \t{}",
+            format_duration(app.get_total_pause_time())
+        )
+        .into(),
     ];
     let para = Paragraph::new(text)
         .style(Style::new().fg(Color::Yellow))
@@ -131,12 +285,60 @@ fn render_task(app: &App, f: &mut Frame, area: Rect) {
     let block = standard_block("Active Task");
     let inner = block.inner(area);
 
-    let message = app.get_current_task_name().unwrap_or("No active task.");
-    let message = Paragraph::new(message).block(Block::new().padding(Padding::horizontal(1)));
+    let message =
+        Paragraph::new(task_panel_lines(app)).block(Block::new().padding(Padding::horizontal(1)));
     f.render_widget(message, inner);
     f.render_widget(block, area);
 }
 
+/// The selected task with its details, then its note and its checklist, numbered for
+/// ticking.
+fn task_panel_lines(app: &App) -> Vec<Line<'_>> {
+    let Some(task) = app.tasks.get_nth(app.task_widget_state.selected()) else {
+        return vec!["No active task.".into()];
+    };
+    let mut lines = vec![task_line(task)];
+    if let Some(note) = &task.note {
+        lines.push(Line::styled(
+            note.as_str(),
+            Style::new().add_modifier(Modifier::ITALIC),
+        ));
+    }
+    lines.extend(task.checklist.iter().enumerate().map(|(i, item)| {
+        let tick = if item.done { "x" } else { " " };
+        let line = Line::from(format!("{}. [{tick}] {}", i + 1, item.text));
+        if item.done {
+            line.style(Style::new().add_modifier(Modifier::DIM))
+        } else {
+            line
+        }
+    }));
+    lines
+}
+
+/// The task's name, followed by its flags and tags, dimmed.
+fn task_line(task: &Task) -> Line<'_> {
+    let mut details: Vec<String> = vec![];
+    if task.rigid {
+        details.push("rigid".to_string());
+    }
+    if task.optional {
+        details.push("optional".to_string());
+    }
+    if !task.min_duration.is_zero() {
+        details.push(format!("min {}", format_duration(task.min_duration)));
+    }
+    details.extend(task.tags.iter().map(|tag| format!("#{tag}")));
+    let mut line = Line::from(task.name.as_str());
+    if !details.is_empty() {
+        line.push_span(Span::styled(
+            format!("  {}", details.join(" ")),
+            Style::new().add_modifier(Modifier::DIM),
+        ));
+    }
+    line
+}
+
 fn render_timer(app: &App, f: &mut Frame, area: Rect) {
     let layout = Layout::horizontal([Max(7), Fill(1), Max(7)]).flex(Flex::Start);
     let block = standard_block("Timer");
@@ -166,26 +368,78 @@ fn render_timer(app: &App, f: &mut Frame, area: Rect) {
     f.render_widget(block, area);
 }
 
-fn render_table(app: &App, f: &mut Frame, area: Rect) {
-    let block = standard_block("Routine");
-    let rows: Vec<Row> = app
-        .tasks
-        .tasks
-        .iter()
-        .map(|i| generate_task_row(i))
-        .collect();
-    let widths = [
+/// A borderless timer that fits on one line, for when space is tight.
+fn render_timer_line(app: &App, f: &mut Frame, area: Rect) {
+    let [a, b, c] = Layout::horizontal([Length(6), Fill(1), Length(6)]).areas(area);
+
+    let formatter = app.config.clock_format.get_strftime();
+    let start_time = format!("{}", app.get_start_time().format(formatter));
+    f.render_widget(
+        Paragraph::new(start_time).style(Style::new().fg(Color::Yellow)),
+        a,
+    );
+
+    let guage = Gauge::default()
+        .gauge_style(
+            Style::default()
+                .fg(Color::Yellow)
+                .bg(Color::Black)
+                .add_modifier(Modifier::BOLD),
+        )
+        .ratio(app.get_percentage_elapsed());
+    f.render_widget(guage, b);
+
+    let end_time = format!("{}", app.get_projected_end_time().format(formatter));
+    f.render_widget(
+        Paragraph::new(end_time)
+            .alignment(Alignment::Right)
+            .style(Style::new().fg(Color::Yellow)),
+        c,
+    );
+}
+
+fn render_table(app: &App, f: &mut Frame, area: Rect, block: Block) {
+    let original = app.config.original_column;
+    let names = app.routine_names();
+    let mut rows: Vec<Row> = vec![];
+    // where each task's row is, after the separators before it
+    let mut task_rows = vec![];
+    let mut previous = None;
+    for task in &app.tasks.tasks {
+        if names.len() > 1 && previous != Some(task.source) {
+            let name = names.get(task.source).map_or("", String::as_str);
+            rows.push(
+                Row::new(["", name])
+                    .style(Style::new().add_modifier(Modifier::BOLD | Modifier::UNDERLINED)),
+            );
+        }
+        previous = Some(task.source);
+        task_rows.push(rows.len());
+        rows.push(generate_task_row(task, original));
+    }
+    let mut widths = vec![
         Constraint::Length(5),
         Constraint::Length(25),
         Constraint::Length(15),
         Constraint::Length(15),
     ];
-    let mut state = prepare_table_state(app.task_widget_state, block.inner(area));
+    let mut header = vec!["", "Task", "Duration", "Remaining"];
+    if original {
+        widths.push(Constraint::Length(18));
+        header.push("Original");
+    }
+    widths.push(Constraint::Length(BAR_WIDTH));
+    header.push("Progress");
+    let selected = app
+        .task_widget_state
+        .selected()
+        .and_then(|i| task_rows.get(i).copied());
+    let mut state = prepare_table_state(selected, rows.len(), block.inner(area));
     let table = Table::new(rows, widths)
         .column_spacing(1)
         .style(Style::new().fg(Color::Yellow))
         .header(
-            Row::new(vec!["", "Task", "Duration", "Remaining"])
+            Row::new(header)
                 .style(Style::new().add_modifier(Modifier::BOLD))
                 .bottom_margin(1),
         )
@@ -195,8 +449,9 @@ fn render_table(app: &App, f: &mut Frame, area: Rect) {
     f.render_stateful_widget(table, area, &mut state);
 }
 
-fn prepare_table_state(pointer: ListPointer, area: Rect) -> TableState {
-    let selected = pointer.selected().unwrap_or(0);
+/// Scrolls the table so the `selected` row of `length` shows, with room after it.
+fn prepare_table_state(selected: Option<usize>, length: usize, area: Rect) -> TableState {
+    let row = selected.unwrap_or(0);
     // TODO can header_height be calculated? It comes from
     // the header row plus the bottom_margin of the table
     let header_height = 2;
@@ -212,12 +467,12 @@ fn prepare_table_state(pointer: ListPointer, area: Rect) -> TableState {
         3..=4 => 1,
         5.. => 2,
     };
-    let length = pointer.length();
     let max_offset = length.saturating_sub(height);
-    let min_offset = selected.saturating_sub(buffer);
+    let min_offset = row.saturating_sub(buffer);
     let offset = min_offset.clamp(0, max_offset);
-    let state: TableState = pointer.into();
-    state.with_offset(offset)
+    TableState::default()
+        .with_selected(selected)
+        .with_offset(offset)
 }
 
 // TODO move to utility module
@@ -240,17 +495,57 @@ pub fn format_duration(dur: Duration) -> String {
     format!("{h_str}{m_str}{s_str}")
 }
 
-fn generate_task_row(task: &Task) -> Row {
-    let checkbox = match task.status {
+fn checkbox(status: CompletionStatus) -> &'static str {
+    match status {
         CompletionStatus::Done => "[x]",
         CompletionStatus::NotYet => "[ ]",
         CompletionStatus::Skipped => "[-]",
     }
-    .to_string();
+}
+
+/// Width of the progress bar in each row of the routine table.
+const BAR_WIDTH: u16 = 10;
+
+/// A row of the routine table, in red if more time has gone into the task than the routine
+/// planned for it. With `original`, it has the planned duration and how far the task has
+/// been shrunk or grown from it.
+fn generate_task_row(task: &Task, original: bool) -> Row<'_> {
+    let checkbox = checkbox(task.status).to_string();
     let title = task.name.to_string();
     let duration = format_duration(task.duration);
     let remaining = format_duration(task.remaining());
-    Row::new(vec![checkbox, title, duration, remaining])
+    let mut cells = vec![checkbox, title, duration, remaining];
+    if original {
+        let mut cell = format_duration(task.original_duration);
+        if task.duration != task.original_duration {
+            cell += &format!(
+                " {}",
+                summary::format_delta(task.original_duration, task.duration)
+            );
+        }
+        cells.push(cell);
+    }
+    let ratio = if task.duration.is_zero() {
+        0.0_f64
+    } else {
+        task.elapsed.div_duration_f64(task.duration)
+    };
+    cells.push(mini_bar(ratio, BAR_WIDTH.into()));
+    let row = Row::new(cells);
+    if task.elapsed > task.original_duration {
+        row.style(Style::new().fg(Color::Red))
+    } else {
+        row
+    }
+}
+
+/// A bar `width` characters wide, filled `ratio` of the way.
+fn mini_bar(ratio: f64, width: usize) -> String {
+    let filled = (ratio.clamp(0.0_f64, 1.0_f64) * width as f64).round();
+    // the clamp keeps it between 0 and width
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let filled = filled as usize;
+    format!("{}{}", "█".repeat(filled), "·".repeat(width - filled))
 }
 
 fn standard_block(title: &str) -> Block<'_> {
@@ -261,3 +556,296 @@ fn standard_block(title: &str) -> Block<'_> {
         .borders(Borders::ALL)
         .padding(Padding::new(2, 2, 1, 1))
 }
+
+/// A block with only a title, for when there is no space to spare.
+fn minimal_block(title: &str) -> Block<'_> {
+    Block::new().title(title)
+}
+
+/// Like [`standard_block`], but without padding, for when space is tight.
+fn compact_block(title: &str) -> Block<'_> {
+    Block::default()
+        .title(title)
+        .title_alignment(Alignment::Center)
+        .style(Style::new().fg(Color::Yellow))
+        .borders(Borders::ALL)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::app::logging::RoutineLogger;
+    use crate::config::{Config, LayoutPreference};
+    use crate::routine::{task::ChecklistItem, Routine};
+    use ratatui::backend::TestBackend;
+
+    fn test_app(layout: LayoutPreference) -> App {
+        let tasks = Routine::with_tasks(vec![
+            Task::new("brush teeth", 180),
+            Task::new("put on glasses", 60),
+        ]);
+        let config = Config {
+            layout,
+            ..Config::default()
+        };
+        App::with_routine(config, tasks, RoutineLogger::with_writer(std::io::sink()))
+    }
+
+    /// Renders the app into a terminal of the given size and returns what's on screen.
+    fn draw(app: &App, width: u16, height: u16) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal.draw(|f| render(app, f)).unwrap();
+        terminal
+            .backend()
+            .buffer()
+            .content()
+            .chunks(width.into())
+            .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn separators_between_chained_routines() {
+        let mut app = test_app(LayoutPreference::Full);
+        app.routine_paths = vec!["morning.csv".into(), "leave.csv".into()];
+        app.tasks.tasks[1].source = 1;
+        app.next_task();
+        let screen = draw(&app, 80, 24);
+
+        let lines: Vec<&str> = screen.lines().collect();
+        let row = |text: &str| lines.iter().position(|line| line.contains(text)).unwrap();
+        assert!(row("morning.csv") < row("brush teeth"));
+        assert!(row("brush teeth") < row("leave.csv"));
+        assert!(row("leave.csv") < row(">> "));
+        assert!(lines[row(">> ")].contains("put on glasses"));
+    }
+
+    #[test]
+    fn full_layout_on_roomy_terminal() {
+        let screen = draw(&test_app(LayoutPreference::Auto), 80, 24);
+
+        assert!(screen.contains("Timer"));
+        assert!(screen.contains("Active Task"));
+        assert!(screen.contains("put on glasses"));
+    }
+
+    #[test]
+    fn compact_layout_on_short_terminal() {
+        let screen = draw(&test_app(LayoutPreference::Auto), 60, 10);
+
+        assert!(!screen.contains("Timer"));
+        assert!(!screen.contains("Active Task"));
+        assert!(screen.contains("Routine"));
+        assert!(screen.contains("brush teeth"));
+    }
+
+    #[test]
+    fn compact_layout_while_typing() {
+        let mut app = test_app(LayoutPreference::Auto);
+        app.insert_task_start();
+
+        let screen = draw(&app, 60, 10);
+
+        assert!(screen.contains("Insert New Task"));
+        assert!(screen.contains("Routine"));
+    }
+
+    #[test]
+    fn typing_problems_shown_in_the_field() {
+        for layout in [LayoutPreference::Full, LayoutPreference::Minimal] {
+            let mut app = test_app(layout);
+            app.append_task_start();
+            app.text_input.insert_str("floss !soon");
+
+            let screen = draw(&app, 80, 24);
+
+            assert!(screen.contains("unknown flag \"!soon\""));
+            assert!(screen.contains("floss !soon"));
+        }
+    }
+
+    #[test]
+    fn suggestions_under_the_field() {
+        let mut app = test_app(LayoutPreference::Full);
+        app.append_task_start();
+        app.text_input.insert_str("glas");
+
+        let screen = draw(&app, 80, 24);
+
+        assert!(screen.contains("Tab to accept"));
+        assert!(screen.contains("put on glasses  ~1m 0s"));
+    }
+
+    #[test]
+    fn active_task_shows_note_and_checklist() {
+        let mut app = test_app(LayoutPreference::Full);
+        let task = &mut app.tasks.tasks[0];
+        task.note = Some("before the coffee".to_string());
+        task.checklist = vec![ChecklistItem::new("floss"), ChecklistItem::new("rinse")];
+        app.toggle_checklist_item(0);
+
+        let screen = draw(&app, 80, 30);
+
+        assert!(screen.contains("before the coffee"));
+        assert!(screen.contains("1. [x] floss"));
+        assert!(screen.contains("2. [ ] rinse"));
+    }
+
+    #[test]
+    fn focus_view() {
+        let mut app = test_app(LayoutPreference::Full);
+        app.toggle_focus();
+
+        let screen = draw(&app, 80, 24);
+
+        assert!(screen.contains("brush teeth"));
+        assert!(screen.contains("███   ███ ███"));
+        assert!(screen.contains("Next: put on glasses"));
+        assert!(!screen.contains("Routine"));
+    }
+
+    #[test]
+    fn rows_show_progress_and_original() {
+        let mut app = test_app(LayoutPreference::Full);
+        app.config.original_column = true;
+        app.tasks.elapse(Some(0), Duration::from_secs(90));
+        app.tasks
+            .set_deadline_at(app.start_time + Duration::from_secs(120), app.start_time);
+
+        let screen = draw(&app, 120, 24);
+
+        assert!(screen.contains("Original"));
+        assert!(screen.contains("3m 0s -18s"));
+        assert!(screen.contains("1m 0s -12s"));
+        assert!(screen.contains("██████····"));
+    }
+
+    #[test]
+    fn mini_bars() {
+        assert_eq!(mini_bar(0.0, 4), "····");
+        assert_eq!(mini_bar(0.5, 4), "██··");
+        assert_eq!(mini_bar(3.0, 4), "████");
+    }
+
+    #[test]
+    fn full_layout_asks_before_deleting() {
+        let mut app = test_app(LayoutPreference::Auto);
+        app.delete_task_start();
+
+        let screen = draw(&app, 80, 24);
+
+        assert!(screen.contains("Delete \"brush teeth\"? (y/n)"));
+    }
+
+    #[test]
+    fn help_is_drawn_over_the_timer() {
+        let mut app = test_app(LayoutPreference::Auto);
+        app.toggle_help();
+
+        let screen = draw(&app, 80, 24);
+
+        assert!(screen.contains("Timer"));
+        assert!(screen.contains("Skip"));
+    }
+
+    #[test]
+    fn help_while_typing_shows_typing_keys() {
+        let mut app = test_app(LayoutPreference::Auto);
+        app.insert_task_start();
+        app.toggle_help();
+
+        let screen = draw(&app, 80, 24);
+
+        assert!(screen.contains("Help: Insert New Task"));
+        assert!(screen.contains("Submit"));
+        assert!(!screen.contains("Skip"));
+    }
+
+    #[test]
+    fn help_scrolls() {
+        let mut app = test_app(LayoutPreference::Auto);
+        app.toggle_help();
+        app.scroll_help_down();
+
+        let screen = draw(&app, 80, 24);
+
+        assert!(!screen.contains("Complete"));
+        assert!(screen.contains("Skip"));
+    }
+
+    #[test]
+    fn help_stops_scrolling_at_the_last_line() {
+        let mut app = test_app(LayoutPreference::Auto);
+        app.help_height = help_visible_lines(Rect::new(0, 0, 80, 24));
+        app.toggle_help();
+        for _ in 0..50_u8 {
+            app.scroll_help_down();
+        }
+        let bottom = draw(&app, 80, 24);
+        app.scroll_help_up();
+
+        let lines = help_line_count(&app.menu_focus);
+        assert_eq!(
+            usize::from(app.help_scroll) + 1,
+            lines - usize::from(app.help_height)
+        );
+        assert!(bottom.contains("Close Help"));
+        assert_ne!(draw(&app, 80, 24), bottom);
+    }
+
+    #[test]
+    fn pause_shows_how_long() {
+        let mut app = test_app(LayoutPreference::Auto);
+        app.pause();
+
+        let screen = draw(&app, 80, 24);
+
+        assert!(screen.contains("Paused for 0s"));
+    }
+
+    #[test]
+    fn summary_when_routine_is_done() {
+        let mut app = test_app(LayoutPreference::Auto);
+        app.attempt_toggle();
+        app.attempt_skip();
+        app.tick();
+
+        let screen = draw(&app, 80, 24);
+
+        assert!(screen.contains("Routine Complete"));
+        assert!(screen.contains("Planned"));
+        assert!(screen.contains("skipped 1 task"));
+    }
+
+    #[test]
+    fn minimal_layout_on_tiny_terminal() {
+        let screen = draw(&test_app(LayoutPreference::Auto), 18, 4);
+
+        assert!(screen.contains("brush teeth"));
+        assert!(screen.contains("3m 0s left"));
+        assert!(screen.contains("ends"));
+    }
+
+    #[test]
+    fn configured_layout_ignores_size() {
+        let screen = draw(&test_app(LayoutPreference::Minimal), 80, 24);
+
+        assert!(!screen.contains("Routine"));
+        assert!(screen.contains("brush teeth"));
+    }
+
+    #[test]
+    fn forced_full_layout_does_not_panic_when_squashed() {
+        let mut app = test_app(LayoutPreference::Full);
+        app.toggle_debug();
+
+        draw(&app, 10, 3);
+    }
+
+    #[test]
+    fn single_cell_terminal_does_not_panic() {
+        draw(&test_app(LayoutPreference::Auto), 1, 1);
+    }
+}
@@ -0,0 +1,155 @@
+// A view with only the current task, its remaining time in digits big enough to read from
+// across the room, and what comes next.
+
+use ratatui::{
+    prelude::{Constraint::*, *},
+    style::{Color, Modifier, Style},
+    widgets::{LineGauge, Paragraph},
+};
+use std::time::Duration;
+
+use crate::app::App;
+use crate::routine::CompletionStatus;
+
+/// Height of a big glyph.
+const GLYPH_HEIGHT: usize = 5;
+
+/// The big glyph for `c`, a row at a time. Only digits, `:` and `+` have one.
+fn glyph(c: char) -> [&'static str; GLYPH_HEIGHT] {
+    match c {
+        '0' => ["███", "█ █", "█ █", "█ █", "███"],
+        '1' => ["  █", "  █", "  █", "  █", "  █"],
+        '2' => ["███", "  █", "███", "█  ", "███"],
+        '3' => ["███", "  █", "███", "  █", "███"],
+        '4' => ["█ █", "█ █", "███", "  █", "  █"],
+        '5' => ["███", "█  ", "███", "  █", "███"],
+        '6' => ["███", "█  ", "███", "█ █", "███"],
+        '7' => ["███", "  █", "  █", "  █", "  █"],
+        '8' => ["███", "█ █", "███", "█ █", "███"],
+        '9' => ["███", "█ █", "███", "  █", "███"],
+        ':' => [" ", "█", " ", "█", " "],
+        '+' => ["   ", " █ ", "███", " █ ", "   "],
+        _ => [" "; GLYPH_HEIGHT],
+    }
+}
+
+/// `text` in big glyphs, one line per row, with a space between glyphs.
+pub fn big_text(text: &str) -> Vec<String> {
+    (0..GLYPH_HEIGHT)
+        .map(|row| {
+            text.chars()
+                .map(|c| glyph(c)[row])
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect()
+}
+
+/// Like a clock: `m:ss`, or `h:mm:ss` from an hour up.
+pub fn clock(duration: Duration) -> String {
+    let s = duration.as_secs();
+    let (h, m, s) = (s / 3600, s / 60 % 60, s % 60);
+    if h > 0 {
+        format!("{h}:{m:02}:{s:02}")
+    } else {
+        format!("{m}:{s:02}")
+    }
+}
+
+pub fn render(app: &App, f: &mut Frame) {
+    let style = Style::new().fg(Color::Yellow);
+    let selected = app.task_widget_state.selected();
+    let Some(task) = app.tasks.get_nth(selected) else {
+        let message = Paragraph::new("No active task.").style(style).centered();
+        f.render_widget(message, f.area());
+        return;
+    };
+    let [_, name, _, time, _, bar, next, end, _] = Layout::vertical([
+        Fill(1),
+        Length(1),
+        Length(1),
+        Length(5),
+        Length(1),
+        Length(1),
+        Length(1),
+        Length(1),
+        Fill(1),
+    ])
+    .areas(f.area());
+
+    f.render_widget(
+        Paragraph::new(task.name.as_str())
+            .style(style.add_modifier(Modifier::BOLD))
+            .centered(),
+        name,
+    );
+
+    let over = task.elapsed.saturating_sub(task.duration);
+    let (text, time_style) = if over.is_zero() {
+        (clock(task.remaining()), style)
+    } else {
+        (format!("+{}", clock(over)), Style::new().fg(Color::Red))
+    };
+    let lines: Vec<Line> = big_text(&text).into_iter().map(Line::from).collect();
+    f.render_widget(Paragraph::new(lines).style(time_style).centered(), time);
+
+    let ratio = if task.duration.is_zero() {
+        1.0_f64
+    } else {
+        task.elapsed.div_duration_f64(task.duration).min(1.0_f64)
+    };
+    let [_, bar, _] = Layout::horizontal([Fill(1), Percentage(60), Fill(1)]).areas(bar);
+    f.render_widget(
+        LineGauge::default()
+            .filled_style(time_style)
+            .label("")
+            .ratio(ratio),
+        bar,
+    );
+
+    let next_task = app
+        .tasks
+        .tasks
+        .iter()
+        .skip(selected.map_or(0, |i| i + 1))
+        .find(|task| task.status == CompletionStatus::NotYet)
+        .map_or_else(
+            || "Last task".to_string(),
+            |task| format!("Next: {}", task.name),
+        );
+    f.render_widget(Paragraph::new(next_task).style(style).centered(), next);
+
+    let formatter = app.config.clock_format.get_strftime();
+    let end_time = format!("Ends {}", app.get_projected_end_time().format(formatter));
+    f.render_widget(
+        Paragraph::new(end_time)
+            .style(style.add_modifier(Modifier::DIM))
+            .centered(),
+        end,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clock_format() {
+        assert_eq!(clock(Duration::from_secs(65)), "1:05");
+        assert_eq!(clock(Duration::from_secs(3600 + 125)), "1:02:05");
+    }
+
+    #[test]
+    fn big_digits() {
+        assert_eq!(
+            big_text("1:0"),
+            [
+                "  █   ███",
+                "  █ █ █ █",
+                "  █   █ █",
+                "  █ █ █ █",
+                "  █   ███"
+            ]
+        );
+    }
+}
@@ -0,0 +1,124 @@
+use ratatui::{
+    layout::Flex,
+    prelude::{Constraint::*, *},
+    style::{Color, Style},
+    widgets::{Block, Clear, Paragraph},
+};
+
+use crate::app::{App, Confirmation, Menu, Mode};
+
+/// Width of the key column.
+const KEY_WIDTH: usize = 14;
+
+/// The key bindings that do something in `mode`, as (keys, description) pairs.
+pub fn bindings(mode: &Mode) -> Vec<(&'static str, &'static str)> {
+    let mut lines = match mode {
+        Mode::Navigation => vec![
+            ("Enter", "Complete"),
+            ("S", "Skip"),
+            ("J, K", "Navigation"),
+            ("Shift + J, K", "Navigation (skip done)"),
+            ("I", "Insert New Task"),
+            ("A", "Append New Task"),
+            ("E", "Edit Selected Task"),
+            ("X", "Delete Selected Task"),
+            ("R", "Repeat Selected Task Once More"),
+            ("[, ]", "Move Selected Task Up, Down"),
+            ("U", "Undo"),
+            ("Ctrl + R", "Redo"),
+            ("P", "Pause (and submit message for log)"),
+            ("1 - 9", "Tick Checklist Item"),
+            ("F", "Focus View"),
+            ("V", "Back to the Summary, once every task is done"),
+            ("D", "Debug Panel"),
+            ("?", "Help"),
+            ("Ctrl + C", "Quit Accordion Task"),
+        ],
+        Mode::Typing(Menu::Pause) => vec![
+            ("Enter", "Unpause and log message"),
+            ("Esc", "Unpause without logging"),
+        ],
+        Mode::Typing(Menu::InsertTask | Menu::AppendTask) => vec![
+            ("Enter", "Submit"),
+            ("Esc", "Discard"),
+            ("Tab", "Accept suggestion"),
+            ("Up, Down", "Choose suggestion"),
+            ("Syntax", "TASK NAME [__h__m__s]"),
+            ("", "!rigid ?optional #tag min:__m buffer:__s"),
+            ("", "@after TASK NAME, @end, -- note"),
+        ],
+        Mode::Typing(Menu::EditTask) => vec![
+            ("Enter", "Submit"),
+            ("Esc", "Discard"),
+            ("Syntax", "TASK NAME [__h__m__s]"),
+        ],
+        Mode::Summary => vec![
+            ("W", "Save routine, with changes, to its file"),
+            ("Q", "Quit Accordion Task"),
+            ("Esc", "Back to the routine"),
+        ],
+        Mode::Confirm(Confirmation::DeleteTask) => vec![
+            ("Y, Enter", "Delete the task"),
+            ("Any other key", "Keep the task"),
+        ],
+    };
+    lines.push(("F1", "Close Help"));
+    lines
+}
+
+/// Number of lines the help text takes up in `mode`.
+pub fn line_count(mode: &Mode) -> usize {
+    bindings(mode).len()
+}
+
+fn title(mode: &Mode) -> &'static str {
+    match mode {
+        Mode::Navigation => "Help",
+        Mode::Typing(Menu::Pause) => "Help: Paused",
+        Mode::Typing(Menu::InsertTask) => "Help: Insert New Task",
+        Mode::Typing(Menu::AppendTask) => "Help: Append New Task",
+        Mode::Typing(Menu::EditTask) => "Help: Edit Task",
+        Mode::Confirm(_) => "Help: Confirm",
+        Mode::Summary => "Help: Summary",
+    }
+}
+
+/// Draws the help text in a box in the middle of the screen, on top of whatever is there.
+pub fn render(app: &App, f: &mut Frame) {
+    let area = popup_area(f.area());
+    let text: Vec<Line> = bindings(&app.menu_focus)
+        .into_iter()
+        .map(|(keys, description)| format!("{keys:<KEY_WIDTH$}{description}").into())
+        .collect();
+    let block = help_block(&app.menu_focus);
+    // Stop scrolling once the last line is at the bottom.
+    let visible = block.inner(area).height;
+    let max_scroll = u16::try_from(text.len())
+        .unwrap_or(u16::MAX)
+        .saturating_sub(visible);
+    let para = Paragraph::new(text)
+        .style(Style::new().fg(Color::Yellow))
+        .scroll((app.help_scroll.min(max_scroll), 0))
+        .block(block);
+    f.render_widget(Clear, area);
+    f.render_widget(para, area);
+}
+
+/// How many lines of help text fit in a terminal of size `area`.
+pub fn visible_lines(area: Rect) -> u16 {
+    help_block(&Mode::Navigation).inner(popup_area(area)).height
+}
+
+fn help_block(mode: &Mode) -> Block<'static> {
+    super::compact_block(title(mode)).title_bottom(Line::from(" J, K : Scroll ").centered())
+}
+
+fn popup_area(area: Rect) -> Rect {
+    let [area] = Layout::horizontal([Max(60)]).flex(Flex::Center).areas(area);
+    // Most of the height, but never so little that only the borders fit.
+    let height = (area.height * 7 / 10).max(area.height.min(5));
+    let [area] = Layout::vertical([Length(height)])
+        .flex(Flex::Center)
+        .areas(area);
+    area
+}
@@ -0,0 +1,160 @@
+use ratatui::layout::{Constraint::*, Layout, Rect};
+
+use crate::config::LayoutPreference;
+
+/// Height of the timer panel and of the active task panel in the full layout.
+const PANEL_HEIGHT: u16 = 5;
+const DEBUG_HEIGHT: u16 = 15;
+/// Height of a text field with a border around it.
+const FIELD_HEIGHT: u16 = 3;
+/// Height of a routine table that can show its header and at least one task: two for
+/// the border, two for the header, and one for the task.
+const TABLE_MIN_HEIGHT: u16 = 5;
+/// Padding in the full layout's routine table panel.
+const TABLE_PADDING: u16 = 2;
+
+const FULL_MIN_WIDTH: u16 = 40;
+const FULL_MIN_HEIGHT: u16 = 2 * PANEL_HEIGHT + TABLE_MIN_HEIGHT + TABLE_PADDING;
+const COMPACT_MIN_WIDTH: u16 = 20;
+// The timer line, room for the text field while typing, and the routine table.
+const COMPACT_MIN_HEIGHT: u16 = 1 + FIELD_HEIGHT + TABLE_MIN_HEIGHT;
+
+/// How much of the interface to draw.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum LayoutMode {
+    /// Timer, active task, and routine table, each in their own panel.
+    Full,
+    /// A one line timer above the routine table.
+    Compact,
+    /// Only the current task, its remaining time, and the projected end time.
+    Minimal,
+}
+
+impl LayoutMode {
+    /// Picks the layout to draw in `area`, respecting the user's configured preference.
+    pub fn choose(preference: LayoutPreference, area: Rect) -> Self {
+        match preference {
+            LayoutPreference::Auto => Self::fitting(area),
+            LayoutPreference::Full => Self::Full,
+            LayoutPreference::Compact => Self::Compact,
+            LayoutPreference::Minimal => Self::Minimal,
+        }
+    }
+
+    /// The largest layout that fits in `area`.
+    pub fn fitting(area: Rect) -> Self {
+        if area.width >= FULL_MIN_WIDTH && area.height >= FULL_MIN_HEIGHT {
+            Self::Full
+        } else if area.width >= COMPACT_MIN_WIDTH && area.height >= COMPACT_MIN_HEIGHT {
+            Self::Compact
+        } else {
+            Self::Minimal
+        }
+    }
+}
+
+/// Splits `area` into the timer, active task, routine table, and debug panel. The active
+/// task panel grows to fit `task_lines` of text, as long as the routine table still fits.
+/// The debug panel has no height unless `debug` is set.
+pub fn full(area: Rect, debug: bool, task_lines: u16) -> [Rect; 4] {
+    let debug_height = if debug { DEBUG_HEIGHT } else { 0 };
+    let room = area
+        .height
+        .saturating_sub(PANEL_HEIGHT + TABLE_MIN_HEIGHT + TABLE_PADDING);
+    let task_height = (PANEL_HEIGHT - 1)
+        .saturating_add(task_lines)
+        .min(room)
+        .max(PANEL_HEIGHT);
+    Layout::vertical([
+        Length(PANEL_HEIGHT),
+        Length(task_height),
+        Min(0),
+        Length(debug_height),
+    ])
+    .areas(area)
+}
+
+/// Splits `area` into the timer line, text field, and routine table. The text field has no
+/// height unless `typing` is set.
+pub fn compact(area: Rect, typing: bool) -> [Rect; 3] {
+    let field_height = if typing { FIELD_HEIGHT } else { 0 };
+    Layout::vertical([Length(1), Length(field_height), Min(0)]).areas(area)
+}
+
+/// Splits `area` into the text field and the current task summary. The text field has no
+/// height unless `typing` is set, and a line more for `problems` with what was typed.
+pub fn minimal(area: Rect, typing: bool, problems: bool) -> [Rect; 2] {
+    // Borderless: one line for the label and one for the text.
+    let field_height = match (typing, problems) {
+        (false, _) => 0,
+        (true, false) => 2,
+        (true, true) => 3,
+    };
+    Layout::vertical([Length(field_height), Min(0)]).areas(area)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roomy_terminal_is_full() {
+        let area = Rect::new(0, 0, 80, 24);
+
+        assert_eq!(LayoutMode::fitting(area), LayoutMode::Full);
+    }
+
+    #[test]
+    fn short_terminal_is_compact() {
+        let area = Rect::new(0, 0, 80, 10);
+
+        assert_eq!(LayoutMode::fitting(area), LayoutMode::Compact);
+    }
+
+    #[test]
+    fn narrow_terminal_is_compact() {
+        let area = Rect::new(0, 0, 30, 24);
+
+        assert_eq!(LayoutMode::fitting(area), LayoutMode::Compact);
+    }
+
+    #[test]
+    fn tiny_terminal_is_minimal() {
+        let area = Rect::new(0, 0, 15, 3);
+
+        assert_eq!(LayoutMode::fitting(area), LayoutMode::Minimal);
+    }
+
+    #[test]
+    fn preference_overrides_size() {
+        let area = Rect::new(0, 0, 15, 3);
+
+        assert_eq!(
+            LayoutMode::choose(LayoutPreference::Full, area),
+            LayoutMode::Full
+        );
+    }
+
+    #[test]
+    fn full_layout_fits_table_when_debugging_on_short_terminal() {
+        let area = Rect::new(0, 0, 80, 20);
+
+        let [timer, task, table, debug] = full(area, true, 1);
+
+        assert_eq!(timer.height + task.height + table.height + debug.height, 20);
+        assert_eq!(timer.height, PANEL_HEIGHT);
+    }
+
+    #[test]
+    fn task_panel_grows_while_the_table_fits() {
+        let [_, task, ..] = full(Rect::new(0, 0, 80, 40), false, 5);
+        assert_eq!(task.height, PANEL_HEIGHT + 4);
+
+        let [_, task, table, _] = full(Rect::new(0, 0, 80, 20), false, 10);
+        assert_eq!(
+            task.height,
+            20 - PANEL_HEIGHT - TABLE_MIN_HEIGHT - TABLE_PADDING
+        );
+        assert_eq!(table.height, TABLE_MIN_HEIGHT + TABLE_PADDING);
+    }
+}
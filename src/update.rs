@@ -1,23 +1,33 @@
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
-use tui_textarea::Input;
 
-use crate::app::{App, Menu, Mode};
+use crate::app::{App, Confirmation, Menu, Mode};
+use crate::control::ControlCommand;
+use crate::routine::CompletionStatus;
 
 pub fn update(app: &mut App, key_event: KeyEvent) {
+    if app.help_menu {
+        update_help_view(app, key_event);
+        return;
+    }
+    // F1 opens help from anywhere, including while typing, where ? is just text.
+    if key_event.code == KeyCode::F(1) {
+        app.toggle_help();
+        return;
+    }
     match app.menu_focus {
         Mode::Navigation => update_navigation_view(app, key_event),
         Mode::Typing(menu) => update_typing_view(app, key_event, menu),
+        Mode::Confirm(confirmation) => update_confirm_view(app, key_event, confirmation),
+        Mode::Summary => update_summary_view(app, key_event),
     }
 }
 
 fn update_navigation_view(app: &mut App, key_event: KeyEvent) {
     match key_event.code {
         //KeyCode::Esc should close menus and always return you to the main screen
-        KeyCode::Char('c' | 'C') => {
-            if key_event.modifiers == KeyModifiers::CONTROL {
-                app.quit();
-            }
-        }
+        KeyCode::Char('c' | 'C') if key_event.modifiers == KeyModifiers::CONTROL => app.quit(),
+        KeyCode::Char('r') if key_event.modifiers == KeyModifiers::CONTROL => app.redo(),
+        KeyCode::Char('u') => app.undo(),
         KeyCode::Right | KeyCode::Char('j') => app.next_task(),
         KeyCode::Char('J') => app.next_available_task(),
         KeyCode::Left | KeyCode::Char('k') => app.prev_task(),
@@ -26,22 +36,130 @@ fn update_navigation_view(app: &mut App, key_event: KeyEvent) {
         KeyCode::Char('s') => app.attempt_skip(),
         KeyCode::Char('a') => app.append_task_start(),
         KeyCode::Char('i') => app.insert_task_start(),
+        KeyCode::Char('e') => app.edit_task_start(),
+        KeyCode::Char('x') => app.delete_task_start(),
+        KeyCode::Char('r') => app.repeat_task(),
+        KeyCode::Char('[') => app.move_task_up(),
+        KeyCode::Char(']') => app.move_task_down(),
         KeyCode::Char('p') => app.pause(),
         KeyCode::Char('?') => app.toggle_help(),
         KeyCode::Char('d') => app.toggle_debug(),
+        KeyCode::Char('f') => app.toggle_focus(),
+        KeyCode::Char('v') => app.reopen_summary(),
+        KeyCode::Char(c @ '1'..='9') => {
+            // the checklist is numbered from one
+            if let Some(item) = c.to_digit(10).and_then(|n| usize::try_from(n).ok()) {
+                app.toggle_checklist_item(item - 1);
+            }
+        }
         _ => {}
     };
 }
 
 fn update_typing_view(app: &mut App, key_event: KeyEvent, menu: Menu) {
+    match key_event.code {
+        KeyCode::Tab => return app.accept_suggestion(),
+        KeyCode::Down => return app.next_suggestion(),
+        KeyCode::Up => return app.prev_suggestion(),
+        // anything else may change the suggestions
+        _ => app.suggestion = 0,
+    }
+    let field = &mut app.text_input;
+    let control = key_event.modifiers.contains(KeyModifiers::CONTROL);
     match key_event.code {
         KeyCode::Esc => app.cancel_typing(menu),
         // submit
         KeyCode::Enter => app.submit_typing(menu),
-        // TODO there are other ways to make a newline which I need to disable.
-        _ => {
-            app.text_input
-                .input(<crossterm::event::KeyEvent as Into<Input>>::into(key_event));
-        } //_ => {app.text_input.input(key_event.into());},
+        KeyCode::Char('a') if control => field.home(),
+        KeyCode::Char('e') if control => field.end(),
+        KeyCode::Char('u') if control => field.delete_to_start(),
+        KeyCode::Char('w') if control => field.delete_word(),
+        KeyCode::Backspace if key_event.modifiers.contains(KeyModifiers::ALT) => {
+            field.delete_word();
+        }
+        KeyCode::Char(_) if control => {}
+        KeyCode::Char(c) => field.insert(c),
+        KeyCode::Backspace => field.backspace(),
+        KeyCode::Delete => field.delete(),
+        KeyCode::Left => field.left(),
+        KeyCode::Right => field.right(),
+        KeyCode::Home => field.home(),
+        KeyCode::End => field.end(),
+        _ => {}
+    }
+}
+
+fn update_confirm_view(app: &mut App, key_event: KeyEvent, confirmation: Confirmation) {
+    match key_event.code {
+        KeyCode::Char('y' | 'Y') | KeyCode::Enter => app.confirm(confirmation),
+        // anything else backs out, so a stray key can't delete something
+        _ => app.cancel_confirm(),
+    }
+}
+
+fn update_help_view(app: &mut App, key_event: KeyEvent) {
+    match key_event.code {
+        KeyCode::Down | KeyCode::Char('j') => app.scroll_help_down(),
+        KeyCode::Up | KeyCode::Char('k') => app.scroll_help_up(),
+        KeyCode::Esc | KeyCode::F(1) | KeyCode::Char('?' | 'q') => app.toggle_help(),
+        KeyCode::Char('c' | 'C') if key_event.modifiers == KeyModifiers::CONTROL => app.quit(),
+        _ => {}
+    }
+}
+
+fn update_summary_view(app: &mut App, key_event: KeyEvent) {
+    match key_event.code {
+        KeyCode::Char('c' | 'C') if key_event.modifiers == KeyModifiers::CONTROL => app.quit(),
+        // not Enter, which a quick double tap to finish the last task would press
+        KeyCode::Char('q') => app.quit(),
+        KeyCode::Char('w') => app.save_routine(),
+        KeyCode::Esc => app.back_to_routine(),
+        KeyCode::Char('?') => app.toggle_help(),
+        _ => {}
+    }
+}
+
+/// Carries out a command from the control socket, and returns the answer for it.
+pub fn control(app: &mut App, command: ControlCommand) -> Result<String, String> {
+    match (&app.menu_focus, &command) {
+        (_, ControlCommand::Status) => return Ok(app.status().text),
+        (_, ControlCommand::Quit) => {
+            app.quit();
+            return Ok(String::new());
+        }
+        (Mode::Summary, _) => return Err("the routine is over".to_string()),
+        (Mode::Typing(Menu::Pause), ControlCommand::Resume(reason)) => {
+            app.resume(reason);
+            return Ok(String::new());
+        }
+        (Mode::Typing(Menu::Pause), _) => return Err("paused".to_string()),
+        (_, ControlCommand::Resume(_)) => return Err("not paused".to_string()),
+        (Mode::Navigation, _) => (),
+        (Mode::Typing(_) | Mode::Confirm(_), _) => {
+            return Err("busy with something in the terminal".to_string())
+        }
+    }
+    let selected_status = app
+        .tasks
+        .get_nth(app.task_widget_state.selected())
+        .map(|task| task.status);
+    match command {
+        // toggling would undo a task that's already done, which isn't what's asked for
+        ControlCommand::Complete | ControlCommand::Skip
+            if selected_status != Some(CompletionStatus::NotYet) =>
+        {
+            return Err("no unfinished task selected".to_string())
+        }
+        ControlCommand::Complete => app.attempt_toggle(),
+        ControlCommand::Skip => app.attempt_skip(),
+        ControlCommand::Next => app.next_task(),
+        ControlCommand::Prev => app.prev_task(),
+        ControlCommand::Pause => app.pause(),
+        ControlCommand::Add(line) => app
+            .add_task(&line)
+            .map_err(|problems| problems.join("; "))?,
+        // handled above
+        ControlCommand::Status | ControlCommand::Resume(_) | ControlCommand::Quit => (),
     }
+    Ok(app.status().text)
 }